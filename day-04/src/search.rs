@@ -0,0 +1,757 @@
+use grid::{Direction, Vec2};
+use rayon::prelude::*;
+use rayon::ThreadPool;
+
+// the four axes that are scanned, every other direction is covered by
+// searching for the reversed word along the opposite axis
+const AXES: [Direction; 4] = [Direction::E, Direction::S, Direction::SE, Direction::SW];
+
+const ABSENT: u32 = u32::MAX;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Match {
+    pub pattern: usize,
+    pub start: Vec2,
+    pub direction: Direction,
+    // only set by `PatternSearch::with_reflections`, for a pattern that was
+    // flipped left to right before it was rotated
+    pub mirrored: bool,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Entry {
+    pattern: usize,
+    len: usize,
+    reversed: bool,
+}
+
+#[derive(Debug)]
+pub struct WordSearch {
+    entries: Vec<Entry>,
+    // maps a byte to its column in the transition table, 0 is reserved for
+    // bytes that don't appear in any word
+    classes: [u8; 256],
+    stride: usize,
+    transitions: Vec<u32>,
+    outputs: Vec<Vec<u32>>,
+    max_len: usize,
+}
+
+impl WordSearch {
+    pub fn new<I, S>(words: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let words: Vec<Vec<u8>> = words
+            .into_iter()
+            .map(|word| word.as_ref().as_bytes().to_vec())
+            .collect();
+        assert!(
+            words.iter().all(|word| !word.is_empty()),
+            "words should not be empty"
+        );
+
+        let mut classes = [0u8; 256];
+        let mut stride = 1;
+        for &b in words.iter().flatten() {
+            if classes[b as usize] == 0 {
+                assert!(stride < 256, "too many distinct letters");
+                classes[b as usize] = stride as u8;
+                stride += 1;
+            }
+        }
+
+        let mut entries = Vec::new();
+        for (pattern, word) in words.iter().enumerate() {
+            entries.push((
+                word.clone(),
+                Entry {
+                    pattern,
+                    len: word.len(),
+                    reversed: false,
+                },
+            ));
+            // palindromes would otherwise be reported twice for the same cells
+            let reversed = word.iter().rev().copied().collect::<Vec<_>>();
+            if reversed != *word {
+                entries.push((
+                    reversed,
+                    Entry {
+                        pattern,
+                        len: word.len(),
+                        reversed: true,
+                    },
+                ));
+            }
+        }
+
+        // build the trie
+        let mut transitions = vec![ABSENT; stride];
+        let mut outputs: Vec<Vec<u32>> = vec![vec![]];
+        for (id, (bytes, _)) in entries.iter().enumerate() {
+            let mut node = 0;
+            for &b in bytes {
+                let i = node * stride + classes[b as usize] as usize;
+                if transitions[i] == ABSENT {
+                    transitions[i] = outputs.len() as u32;
+                    transitions.extend(std::iter::repeat_n(ABSENT, stride));
+                    outputs.push(vec![]);
+                }
+                node = transitions[i] as usize;
+            }
+            outputs[node].push(id as u32);
+        }
+
+        // fill in the failure transitions breadth first so that every state
+        // has a transition for every class
+        let mut fail = vec![0usize; outputs.len()];
+        let mut queue = std::collections::VecDeque::new();
+        for transition in transitions.iter_mut().take(stride) {
+            match *transition {
+                ABSENT => *transition = 0,
+                child => queue.push_back(child as usize),
+            }
+        }
+        while let Some(node) = queue.pop_front() {
+            for c in 0..stride {
+                let i = node * stride + c;
+                let target = transitions[fail[node] * stride + c];
+                match transitions[i] {
+                    ABSENT => transitions[i] = target,
+                    child => {
+                        let child = child as usize;
+                        fail[child] = target as usize;
+                        let inherited = outputs[target as usize].clone();
+                        outputs[child].extend(inherited);
+                        queue.push_back(child);
+                    }
+                }
+            }
+        }
+
+        Self {
+            max_len: words.iter().map(Vec::len).max().unwrap_or(1),
+            entries: entries.into_iter().map(|(_, entry)| entry).collect(),
+            classes,
+            stride,
+            transitions,
+            outputs,
+        }
+    }
+
+    pub fn find_all(&self, input: &str) -> Vec<Match> {
+        let rows = rows(input);
+        let mut matches = Vec::new();
+        self.scan_band(&rows, 0, rows.len(), &mut |m| matches.push(m));
+        matches
+    }
+
+    pub fn count(&self, input: &str) -> usize {
+        let rows = rows(input);
+        let mut counter = 0;
+        self.scan_band(&rows, 0, rows.len(), &mut |_| counter += 1);
+        counter
+    }
+
+    pub fn par_find_all(&self, input: &str, parallelism: &Parallelism) -> Vec<Match> {
+        let rows = rows(input);
+        parallelism.bands(rows.len(), |top, bottom| {
+            let mut matches = Vec::new();
+            self.scan_band(&rows, top, bottom, &mut |m| matches.push(m));
+            matches
+        })
+    }
+
+    pub fn par_count(&self, input: &str, parallelism: &Parallelism) -> usize {
+        let rows = rows(input);
+        parallelism
+            .bands(rows.len(), |top, bottom| {
+                let mut counter = 0;
+                self.scan_band(&rows, top, bottom, &mut |_| counter += 1);
+                vec![counter]
+            })
+            .into_iter()
+            .sum()
+    }
+
+    // reports every match whose topmost cell lies in `top..bottom`. The band
+    // is read `max_len - 1` rows past the bottom so that matches crossing
+    // into the next band are still found, but only by this band.
+    fn scan_band(&self, rows: &[&[u8]], top: usize, bottom: usize, f: &mut impl FnMut(Match)) {
+        let end = (bottom + self.max_len - 1).min(rows.len());
+        let band = &rows[top..end];
+        let limit = (bottom - top) as i32;
+        for axis in AXES {
+            for start in line_starts(band, axis) {
+                if axis == Direction::E && start.y >= limit {
+                    continue;
+                }
+                self.scan_line(band, start, axis, top as i32, limit, f);
+            }
+        }
+    }
+
+    fn scan_line(
+        &self,
+        rows: &[&[u8]],
+        start: Vec2,
+        axis: Direction,
+        offset_y: i32,
+        limit: i32,
+        f: &mut impl FnMut(Match),
+    ) {
+        let step = Vec2::from(axis);
+        let mut state = 0;
+        let mut position = start.clone();
+        let mut i = 0;
+        while let Some(b) = byte_at(rows, &position) {
+            let class = self.classes[b as usize] as usize;
+            state = self.transitions[state * self.stride + class] as usize;
+            for &id in &self.outputs[state] {
+                let entry = self.entries[id as usize];
+                // single letters read the same along every axis
+                if entry.len == 1 && axis != Direction::E {
+                    continue;
+                }
+                // every axis points downwards, so the first cell of the line
+                // segment is the topmost one
+                let first = (i + 1 - entry.len) as i32;
+                if start.y + step.y * first >= limit {
+                    continue;
+                }
+                let offset = if entry.reversed { i as i32 } else { first };
+                f(Match {
+                    pattern: entry.pattern,
+                    start: Vec2::new(
+                        start.x + step.x * offset,
+                        start.y + step.y * offset + offset_y,
+                    ),
+                    mirrored: false,
+                    direction: if entry.reversed { opposite(axis) } else { axis },
+                });
+            }
+            position = &position + &step;
+            i += 1;
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Pattern {
+    width: usize,
+    height: usize,
+    // `None` is a wildcard that matches any letter
+    cells: Vec<Option<u8>>,
+}
+
+impl Pattern {
+    fn parse(input: &str, wildcard: char) -> Self {
+        let lines: Vec<&str> = input.lines().filter(|line| !line.is_empty()).collect();
+        let width = lines.first().expect("pattern should not be empty").len();
+        assert!(
+            lines.iter().all(|line| line.len() == width),
+            "pattern should be rectangular"
+        );
+        let cells = lines
+            .iter()
+            .flat_map(|line| line.bytes())
+            .map(|b| (b as char != wildcard).then_some(b))
+            .collect();
+
+        Self {
+            width,
+            height: lines.len(),
+            cells,
+        }
+    }
+
+    // rotates a quarter turn clockwise
+    fn rotate(&self) -> Self {
+        let mut cells = Vec::with_capacity(self.cells.len());
+        for y in 0..self.width {
+            for x in 0..self.height {
+                cells.push(self.cells[(self.height - 1 - x) * self.width + y]);
+            }
+        }
+
+        Self {
+            width: self.height,
+            height: self.width,
+            cells,
+        }
+    }
+
+    // flips left to right
+    fn mirror(&self) -> Self {
+        let cells = self
+            .cells
+            .chunks(self.width)
+            .flat_map(|row| row.iter().rev().copied())
+            .collect();
+
+        Self {
+            width: self.width,
+            height: self.height,
+            cells,
+        }
+    }
+
+    fn matches_at(&self, rows: &[&[u8]], x: usize, y: usize) -> bool {
+        self.cells.iter().enumerate().all(|(i, cell)| match cell {
+            Some(b) => rows[y + i / self.width][x + i % self.width] == *b,
+            None => true,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Orientation {
+    pattern: usize,
+    // where the pattern's top row points to after rotating
+    direction: Direction,
+    mirrored: bool,
+    cells: Pattern,
+}
+
+// Patterns are only matched in their four rotations unless reflections are
+// asked for. Wildcards rule out sharing work between patterns the way
+// `WordSearch` does, so every orientation is tried at every cell and the
+// cost grows with the number and size of the patterns.
+#[derive(Debug)]
+pub struct PatternSearch {
+    // each pattern in every distinct orientation
+    orientations: Vec<Orientation>,
+}
+
+impl PatternSearch {
+    pub fn new<I, S>(patterns: I, wildcard: char) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut search = Self {
+            orientations: Vec::new(),
+        };
+        for (index, pattern) in patterns.into_iter().enumerate() {
+            let mut cells = Pattern::parse(pattern.as_ref(), wildcard);
+            for direction in [Direction::E, Direction::S, Direction::W, Direction::N] {
+                search.insert(Orientation {
+                    pattern: index,
+                    direction,
+                    mirrored: false,
+                    cells: cells.clone(),
+                });
+                cells = cells.rotate();
+            }
+        }
+
+        search
+    }
+
+    // also matches every pattern flipped left to right, in each rotation
+    pub fn with_reflections(mut self) -> Self {
+        for orientation in self.orientations.clone() {
+            if !orientation.mirrored {
+                self.insert(Orientation {
+                    mirrored: true,
+                    cells: orientation.cells.mirror(),
+                    ..orientation
+                });
+            }
+        }
+        self
+    }
+
+    // symmetric patterns would otherwise match the same cells more than once
+    fn insert(&mut self, orientation: Orientation) {
+        if !self
+            .orientations
+            .iter()
+            .any(|o| o.pattern == orientation.pattern && o.cells == orientation.cells)
+        {
+            self.orientations.push(orientation);
+        }
+    }
+
+    // matches start at the top left corner of the rotated pattern
+    pub fn find_all(&self, input: &str) -> Vec<Match> {
+        let rows = rows(input);
+        let mut matches = Vec::new();
+        self.scan_band(&rows, 0, rows.len(), &mut |m| matches.push(m));
+        matches
+    }
+
+    pub fn count(&self, input: &str) -> usize {
+        let rows = rows(input);
+        let mut counter = 0;
+        self.scan_band(&rows, 0, rows.len(), &mut |_| counter += 1);
+        counter
+    }
+
+    pub fn par_find_all(&self, input: &str, parallelism: &Parallelism) -> Vec<Match> {
+        let rows = rows(input);
+        parallelism.bands(rows.len(), |top, bottom| {
+            let mut matches = Vec::new();
+            self.scan_band(&rows, top, bottom, &mut |m| matches.push(m));
+            matches
+        })
+    }
+
+    pub fn par_count(&self, input: &str, parallelism: &Parallelism) -> usize {
+        let rows = rows(input);
+        parallelism
+            .bands(rows.len(), |top, bottom| {
+                let mut counter = 0;
+                self.scan_band(&rows, top, bottom, &mut |_| counter += 1);
+                vec![counter]
+            })
+            .into_iter()
+            .sum()
+    }
+
+    // reports every match whose top left corner lies in `top..bottom`
+    fn scan_band(&self, rows: &[&[u8]], top: usize, bottom: usize, f: &mut impl FnMut(Match)) {
+        let cols = rows.first().map_or(0, |row| row.len());
+        for orientation in &self.orientations {
+            let cells = &orientation.cells;
+            if cells.height > rows.len() || cells.width > cols {
+                continue;
+            }
+            for y in top..bottom.min(rows.len() - cells.height + 1) {
+                for x in 0..=cols - cells.width {
+                    if cells.matches_at(rows, x, y) {
+                        f(Match {
+                            pattern: orientation.pattern,
+                            start: Vec2::new(x as i32, y as i32),
+                            direction: orientation.direction,
+                            mirrored: orientation.mirrored,
+                        });
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Parallelism {
+    band_height: usize,
+    pool: Option<ThreadPool>,
+}
+
+impl Parallelism {
+    // runs on rayon's global thread pool unless `with_threads` is used
+    pub fn new(band_height: usize) -> Self {
+        assert!(band_height > 0, "bands should contain at least one row");
+        Self {
+            band_height,
+            pool: None,
+        }
+    }
+
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .expect("should be able to build thread pool");
+        self.pool = Some(pool);
+        self
+    }
+
+    fn bands<T, F>(&self, rows: usize, f: F) -> Vec<T>
+    where
+        T: Send,
+        F: Fn(usize, usize) -> Vec<T> + Sync,
+    {
+        let run = || {
+            (0..rows.div_ceil(self.band_height))
+                .into_par_iter()
+                .flat_map_iter(|band| {
+                    let top = band * self.band_height;
+                    f(top, (top + self.band_height).min(rows))
+                })
+                .collect()
+        };
+        match &self.pool {
+            Some(pool) => pool.install(run),
+            None => run(),
+        }
+    }
+}
+
+fn rows(input: &str) -> Vec<&[u8]> {
+    let rows: Vec<&[u8]> = input
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(str::as_bytes)
+        .collect();
+    if let Some(first) = rows.first() {
+        assert!(
+            rows.iter().all(|row| row.len() == first.len()),
+            "grid should be rectangular"
+        );
+    }
+    rows
+}
+
+#[inline]
+fn byte_at(rows: &[&[u8]], position: &Vec2) -> Option<u8> {
+    let row = rows.get(usize::try_from(position.y).ok()?)?;
+    row.get(usize::try_from(position.x).ok()?).copied()
+}
+
+fn line_starts(rows: &[&[u8]], axis: Direction) -> Vec<Vec2> {
+    let height = rows.len() as i32;
+    let width = rows.first().map_or(0, |row| row.len()) as i32;
+    let top = (0..width).map(|x| Vec2::new(x, 0));
+    match axis {
+        Direction::E => (0..height).map(|y| Vec2::new(0, y)).collect(),
+        Direction::S => top.collect(),
+        Direction::SE => top.chain((1..height).map(|y| Vec2::new(0, y))).collect(),
+        Direction::SW => top
+            .chain((1..height).map(|y| Vec2::new(width - 1, y)))
+            .collect(),
+        _ => unreachable!("only the four forward axes are scanned"),
+    }
+}
+
+fn opposite(direction: Direction) -> Direction {
+    match direction {
+        Direction::N => Direction::S,
+        Direction::NE => Direction::SW,
+        Direction::E => Direction::W,
+        Direction::SE => Direction::NW,
+        Direction::S => Direction::N,
+        Direction::SW => Direction::NE,
+        Direction::W => Direction::E,
+        Direction::NW => Direction::SE,
+    }
+}
+
+// deterministic grid of puzzle letters for testing and benchmarking
+#[cfg(any(test, feature = "bench"))]
+fn generate(width: usize, height: usize, seed: u64) -> String {
+    let mut state = seed.max(1);
+    let mut grid = String::with_capacity((width + 1) * height);
+    for _ in 0..height {
+        for _ in 0..width {
+            // xorshift64
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            grid.push(['X', 'M', 'A', 'S'][(state >> 32) as usize % 4]);
+        }
+        grid.push('\n');
+    }
+    grid
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "MMMSXXMASM
+MSAMXMSMSA
+AMXSXMAAMM
+MSAMASMSMX
+XMASAMXAMM
+XXAMMXXAMA
+SMSMSASXSS
+SAXAMASAAA
+MAMMMXMMMM
+MXMXAXMASX";
+
+    #[test]
+    fn test_word_search() {
+        assert_eq!(WordSearch::new(["XMAS"]).count(EXAMPLE), 18);
+
+        let matches = WordSearch::new(["SAMX", "MAS", "XM"]).find_all(EXAMPLE);
+        assert_eq!(matches.iter().filter(|m| m.pattern == 0).count(), 18);
+        assert!(matches.contains(&Match {
+            pattern: 0,
+            start: Vec2::new(9, 6),
+            direction: Direction::N,
+            mirrored: false,
+        }));
+        assert!(matches.contains(&Match {
+            pattern: 2,
+            start: Vec2::new(4, 0),
+            direction: Direction::SE,
+            mirrored: false,
+        }));
+    }
+
+    #[test]
+    fn test_word_search_matches_naive() {
+        let words = ["AM", "MAS", "AXA", "SAS", "MMM", "X", "XMASAMX"];
+        let matches = WordSearch::new(words).find_all(EXAMPLE);
+        let rows = rows(EXAMPLE);
+
+        let mut expected = 0;
+        for (i, word) in words.iter().enumerate() {
+            let directions: &[Direction] = match word.len() {
+                1 => &[Direction::E],
+                _ => &[
+                    Direction::N,
+                    Direction::NE,
+                    Direction::E,
+                    Direction::SE,
+                    Direction::S,
+                    Direction::SW,
+                    Direction::W,
+                    Direction::NW,
+                ],
+            };
+            let reversed = word.chars().rev().collect::<String>();
+            for y in 0..rows.len() as i32 {
+                for x in 0..rows[0].len() as i32 {
+                    for &direction in directions {
+                        // palindromes are only reported in one of the two opposite directions
+                        if reversed == *word && AXES.contains(&opposite(direction)) {
+                            continue;
+                        }
+                        let step = Vec2::from(direction);
+                        let found = word.bytes().enumerate().all(|(n, b)| {
+                            let n = n as i32;
+                            byte_at(&rows, &Vec2::new(x + step.x * n, y + step.y * n)) == Some(b)
+                        });
+                        if found {
+                            expected += 1;
+                            assert!(matches.contains(&Match {
+                                pattern: i,
+                                start: Vec2::new(x, y),
+                                direction,
+                                mirrored: false,
+                            }));
+                        }
+                    }
+                }
+            }
+        }
+        assert_eq!(matches.len(), expected);
+    }
+
+    // the same grid size as the benchmarks, run with `cargo test --release -- --ignored`
+    #[test]
+    #[ignore]
+    fn test_bands_count_matches_once_large() {
+        let grid = generate(10_000, 10_000, 2024);
+        let words = WordSearch::new(["XMAS"]);
+        let patterns = PatternSearch::new(["M.S\n.A.\nM.S"], '.');
+
+        let parallelism = Parallelism::new(256).with_threads(4);
+        assert_eq!(words.par_count(&grid, &parallelism), words.count(&grid));
+        assert_eq!(
+            patterns.par_count(&grid, &parallelism),
+            patterns.count(&grid)
+        );
+    }
+
+    #[test]
+    fn test_bands_count_matches_once() {
+        use std::collections::HashSet;
+
+        let grid = generate(157, 131, 2024);
+        let words = WordSearch::new(["XMAS", "MAS", "SAMXMAS", "A"]);
+        let patterns = PatternSearch::new(["M.S\n.A.\nM.S", "XM\nAS"], '.');
+
+        let expected_words = words.find_all(&grid);
+        let expected_patterns = patterns.find_all(&grid);
+        assert_eq!(
+            expected_words.iter().collect::<HashSet<_>>().len(),
+            expected_words.len()
+        );
+
+        for band_height in [1, 2, 3, 6, 7, 64, 1000] {
+            let parallelism = Parallelism::new(band_height).with_threads(3);
+
+            let found = words.par_find_all(&grid, &parallelism);
+            assert_eq!(found.len(), expected_words.len());
+            assert_eq!(
+                found.iter().collect::<HashSet<_>>(),
+                expected_words.iter().collect::<HashSet<_>>()
+            );
+            assert_eq!(words.par_count(&grid, &parallelism), expected_words.len());
+
+            let found = patterns.par_find_all(&grid, &parallelism);
+            assert_eq!(
+                found.iter().collect::<HashSet<_>>(),
+                expected_patterns.iter().collect::<HashSet<_>>()
+            );
+            assert_eq!(
+                patterns.par_count(&grid, &parallelism),
+                expected_patterns.len()
+            );
+        }
+    }
+
+    #[test]
+    fn test_pattern_search() {
+        let search = PatternSearch::new(["M.S\n.A.\nM.S"], '.');
+        assert_eq!(search.count(EXAMPLE), 9);
+
+        // symmetric patterns only match once per location
+        let search = PatternSearch::new(["XX", "A"], '.');
+        let matches = search.find_all(EXAMPLE);
+        assert_eq!(matches.iter().filter(|m| m.pattern == 0).count(), 3 + 5);
+        assert_eq!(
+            matches.iter().filter(|m| m.pattern == 1).count(),
+            EXAMPLE.matches('A').count()
+        );
+    }
+
+    #[test]
+    fn test_pattern_search_reflections() {
+        // the X-MAS is its own mirror image, so nothing new turns up
+        let search = PatternSearch::new(["M.S\n.A.\nM.S"], '.').with_reflections();
+        assert_eq!(search.count(EXAMPLE), 9);
+
+        // no rotation of this pattern is its mirror image
+        let search = PatternSearch::new(["AB\nC."], '.');
+        assert_eq!(search.count("BAX\nXCX"), 0);
+        assert_eq!(
+            search.with_reflections().find_all("BAX\nXCX"),
+            vec![Match {
+                pattern: 0,
+                start: Vec2::new(0, 0),
+                direction: Direction::E,
+                mirrored: true,
+            }]
+        );
+    }
+}
+
+#[cfg(feature = "bench")]
+pub mod benchmarks {
+    use super::{generate, Parallelism, PatternSearch, WordSearch};
+    use std::sync::LazyLock;
+
+    static GRID: LazyLock<String> = LazyLock::new(|| generate(10_000, 10_000, 2024));
+
+    #[divan::bench(sample_count = 5, sample_size = 1)]
+    fn bench_words(bencher: divan::Bencher) {
+        let search = WordSearch::new(["XMAS"]);
+        bencher.bench(|| search.count(&GRID));
+    }
+
+    #[divan::bench(args = [1, 2, 4, 8], sample_count = 5, sample_size = 1)]
+    fn bench_words_parallel(bencher: divan::Bencher, threads: usize) {
+        let search = WordSearch::new(["XMAS"]);
+        let parallelism = Parallelism::new(256).with_threads(threads);
+        bencher.bench(|| search.par_count(&GRID, &parallelism));
+    }
+
+    #[divan::bench(sample_count = 5, sample_size = 1)]
+    fn bench_patterns(bencher: divan::Bencher) {
+        let search = PatternSearch::new(["M.S\n.A.\nM.S"], '.');
+        bencher.bench(|| search.count(&GRID));
+    }
+
+    #[divan::bench(args = [1, 2, 4, 8], sample_count = 5, sample_size = 1)]
+    fn bench_patterns_parallel(bencher: divan::Bencher, threads: usize) {
+        let search = PatternSearch::new(["M.S\n.A.\nM.S"], '.');
+        let parallelism = Parallelism::new(256).with_threads(threads);
+        bencher.bench(|| search.par_count(&GRID, &parallelism));
+    }
+}
@@ -0,0 +1,4 @@
+fn main() {
+    let result = day_05::repair::run();
+    print!("{}", result);
+}
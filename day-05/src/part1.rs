@@ -1,3 +1,5 @@
+use crate::rules::{Page, RuleGraph};
+
 const INPUT: &str = include_str!("input.txt");
 
 #[tracing::instrument(level = "trace", skip())]
@@ -6,43 +8,24 @@ pub fn run() -> String {
 }
 
 #[tracing::instrument(level = "trace", skip(input))]
-fn process(input: &str) -> u32 {
+fn process(input: &str) -> Page {
     let (ordering, pages) = input.split_once("\n\n").unwrap();
 
-    let lookup: [u128; 100] = ordering
-        .lines()
-        .map(|line| {
-            line.split_once('|').map(|(a, b)| {
-                (
-                    a.parse::<u8>().expect("should be valid"),
-                    b.parse::<u8>().expect("should be valid"),
-                )
-            })
-        })
-        .fold([0; 100], |mut accumulator, pair| {
-            if let Some((key, value)) = pair {
-                accumulator[key as usize] += 1 << value;
-            }
-            accumulator
-        });
+    let rules = RuleGraph::parse(ordering);
 
-    let result: u32 = pages
+    let result: Page = pages
         .lines()
         .filter_map(|line| {
-            let is_sorted = line
+            let pages: Vec<Page> = line
                 .split(',')
-                .map(|page| page.parse::<u8>().expect("should be valid"))
-                .is_sorted_by(|key, b| lookup[*key as usize] & (1 << b) != 0);
+                .map(|page| page.parse().expect("should be valid"))
+                .collect();
 
-            if !is_sorted {
+            if !rules.is_ordered(&pages) {
                 return None;
             }
 
-            let middle_index = line.split(',').count() / 2;
-
-            line.split(',')
-                .nth(middle_index)
-                .and_then(|n| n.parse::<u32>().ok())
+            pages.get(pages.len() / 2).copied()
         })
         .sum();
 
@@ -1,3 +1,5 @@
+use crate::rules::{Page, RuleGraph};
+
 const INPUT: &str = include_str!("input.txt");
 
 #[tracing::instrument(level = "trace", skip())]
@@ -6,55 +8,32 @@ pub fn run() -> String {
 }
 
 #[tracing::instrument(level = "trace", skip(input))]
-fn process(input: &str) -> u32 {
+fn process(input: &str) -> Page {
     let (ordering, pages) = input.split_once("\n\n").unwrap();
 
-    let lookup: [u128; 100] = ordering
-        .lines()
-        .map(|line| {
-            line.split_once('|').map(|(a, b)| {
-                (
-                    a.parse::<u8>().expect("should be valid"),
-                    b.parse::<u8>().expect("should be valid"),
-                )
-            })
-        })
-        .fold([0; 100], |mut accumulator, pair| {
-            if let Some((key, value)) = pair {
-                accumulator[key as usize] += 1 << value;
-            }
-            accumulator
-        });
+    let rules = RuleGraph::parse(ordering);
 
     let result = pages
         .lines()
         .filter_map(|line| {
-            let pages: Vec<u8> = line
+            let pages: Vec<Page> = line
                 .split(',')
-                .map(|page| page.parse::<u8>().expect("should be valid"))
+                .map(|page| page.parse().expect("should be valid"))
                 .collect();
 
-            let is_sorted = pages.is_sorted_by(|key, b| lookup[*key as usize] & (1 << b) != 0);
-
-            if is_sorted {
+            if rules.is_ordered(&pages) {
                 return None;
             }
 
-            let mut pages = pages;
-
-            pages.sort_by(|a, b| {
-                let value = lookup[*a as usize];
-                match value & (1 << b) != 0 {
-                    true => std::cmp::Ordering::Less,
-                    false => std::cmp::Ordering::Greater,
-                }
-            });
+            let pages = rules
+                .order(&pages)
+                .unwrap_or_else(|error| panic!("update {line} can't be ordered, {error}"));
 
             // find middle pages.
             let len = pages.len();
             let index = len / 2;
             let middle_page = pages.get(index).expect("index exists");
-            Some(*middle_page as u32)
+            Some(*middle_page)
         })
         .sum();
 
@@ -0,0 +1,351 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fmt;
+
+use crate::rules::{OrderError, Page, RuleGraph};
+
+const INPUT: &str = include_str!("input.txt");
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Move {
+    pub page: Page,
+    // `None` moves the page to the front of the update
+    pub after: Option<Page>,
+}
+
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.after {
+            Some(after) => write!(f, "move {} after {}", self.page, after),
+            None => write!(f, "move {} to the front", self.page),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Repair {
+    pub order: Vec<Page>,
+    // the longest subsequence of the update that is already in order, these
+    // pages stay where they are
+    pub kept: Vec<Page>,
+    // applied one after the other, these turn the update into `order`
+    pub moves: Vec<Move>,
+}
+
+impl Repair {
+    pub fn apply(&self, update: &[Page]) -> Vec<Page> {
+        let mut pages = update.to_vec();
+        for m in &self.moves {
+            let from = pages
+                .iter()
+                .position(|&page| page == m.page)
+                .expect("moved page should be in the update");
+            pages.remove(from);
+            let to = match m.after {
+                Some(after) => {
+                    pages
+                        .iter()
+                        .position(|&page| page == after)
+                        .expect("page should be in the update")
+                        + 1
+                }
+                None => 0,
+            };
+            pages.insert(to, m.page);
+        }
+        pages
+    }
+}
+
+impl RuleGraph {
+    // finds the fewest pages to move to make the update valid. The pages that
+    // stay put keep their relative order, so no kept page may have to come,
+    // directly or through other pages of the update, before an earlier kept page.
+    pub fn repair(&self, update: &[Page]) -> Result<Repair, OrderError> {
+        // reports duplicate pages and cycles
+        self.order(update)?;
+
+        let n = update.len();
+        let successors: Vec<Vec<usize>> = update
+            .iter()
+            .map(|&page| {
+                (0..n)
+                    .filter(|&j| self.must_precede(page, update[j]))
+                    .collect()
+            })
+            .collect();
+
+        // reaches[i][j] when page i has to come before page j
+        let mut reaches = vec![vec![false; n]; n];
+        for (i, row) in reaches.iter_mut().enumerate() {
+            let mut stack = successors[i].clone();
+            while let Some(j) = stack.pop() {
+                if !row[j] {
+                    row[j] = true;
+                    stack.extend(&successors[j]);
+                }
+            }
+        }
+
+        // a later page that has to come before an earlier one is an inverted
+        // pair. Inversions are transitive, so the largest set of pages without
+        // one between them is a maximum antichain of that order.
+        let inverted: Vec<Vec<usize>> = (0..n)
+            .map(|j| (0..j).filter(|&i| reaches[j][i]).collect())
+            .collect();
+        let kept_indices = maximum_antichain(&inverted);
+        let kept: Vec<Page> = kept_indices.iter().map(|&i| update[i]).collect();
+
+        // sort again with the kept pages chained together in their current order
+        let mut successors = successors;
+        for pair in kept_indices.windows(2) {
+            successors[pair[0]].push(pair[1]);
+        }
+        let mut in_degree = vec![0; n];
+        for &j in successors.iter().flatten() {
+            in_degree[j] += 1;
+        }
+        let mut ready: BinaryHeap<Reverse<usize>> =
+            (0..n).filter(|&i| in_degree[i] == 0).map(Reverse).collect();
+        let mut order = Vec::with_capacity(n);
+        while let Some(Reverse(i)) = ready.pop() {
+            order.push(update[i]);
+            for &j in &successors[i] {
+                in_degree[j] -= 1;
+                if in_degree[j] == 0 {
+                    ready.push(Reverse(j));
+                }
+            }
+        }
+        debug_assert_eq!(order.len(), n, "kept pages should not form a cycle");
+
+        // moving every other page right behind its predecessor in the new
+        // order, in that order, leaves the update in the new order
+        let moves = order
+            .iter()
+            .enumerate()
+            .filter(|(_, page)| !kept.contains(page))
+            .map(|(i, &page)| Move {
+                page,
+                after: i.checked_sub(1).map(|i| order[i]),
+            })
+            .collect();
+
+        Ok(Repair { order, kept, moves })
+    }
+}
+
+// `below[u]` lists every `v` with `v < u` in a strict partial order. By
+// Dilworth and König, the elements whose left copy is reachable from an
+// unmatched left vertex, while their right copy isn't, form a largest
+// antichain once the bipartite graph of the order is maximally matched.
+fn maximum_antichain(below: &[Vec<usize>]) -> Vec<usize> {
+    fn augment(
+        u: usize,
+        below: &[Vec<usize>],
+        seen: &mut [bool],
+        matched_left: &mut [Option<usize>],
+    ) -> bool {
+        for &v in &below[u] {
+            if !seen[v] {
+                seen[v] = true;
+                if matched_left[v].is_none_or(|w| augment(w, below, seen, matched_left)) {
+                    matched_left[v] = Some(u);
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    let n = below.len();
+
+    // matched_left[v] is the left vertex matched to the right vertex v
+    let mut matched_left: Vec<Option<usize>> = vec![None; n];
+    for u in 0..n {
+        augment(u, below, &mut vec![false; n], &mut matched_left);
+    }
+    let mut is_matched = vec![false; n];
+    for &u in matched_left.iter().flatten() {
+        is_matched[u] = true;
+    }
+
+    // alternating paths from the unmatched left vertices
+    let mut left = vec![false; n];
+    let mut right = vec![false; n];
+    let mut stack: Vec<usize> = (0..n).filter(|&u| !is_matched[u]).collect();
+    while let Some(u) = stack.pop() {
+        if left[u] {
+            continue;
+        }
+        left[u] = true;
+        for &v in &below[u] {
+            if !right[v] {
+                right[v] = true;
+                if let Some(w) = matched_left[v] {
+                    stack.push(w);
+                }
+            }
+        }
+    }
+
+    (0..n).filter(|&x| left[x] && !right[x]).collect()
+}
+
+#[tracing::instrument(level = "trace", skip())]
+pub fn run() -> String {
+    process(INPUT)
+}
+
+#[tracing::instrument(level = "trace", skip(input))]
+fn process(input: &str) -> String {
+    let (ordering, pages) = input.split_once("\n\n").unwrap();
+
+    let rules = RuleGraph::parse(ordering);
+
+    let mut report = String::new();
+    for line in pages.lines() {
+        let pages: Vec<Page> = line
+            .split(',')
+            .map(|page| page.parse().expect("should be valid"))
+            .collect();
+
+        if rules.is_ordered(&pages) {
+            continue;
+        }
+
+        match rules.repair(&pages) {
+            Ok(repair) => {
+                let moves: Vec<String> = repair.moves.iter().map(Move::to_string).collect();
+                report.push_str(&format!("{line}: {}\n", moves.join(", ")));
+            }
+            Err(error) => report.push_str(&format!("{line}: {error}\n")),
+        }
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_works() {
+        let result = process(
+            "47|53
+97|13
+97|61
+97|47
+75|29
+61|13
+75|53
+29|13
+97|29
+53|29
+61|53
+97|53
+61|29
+47|13
+75|47
+97|75
+47|61
+75|61
+47|29
+75|13
+53|13
+
+75,47,61,53,29
+97,61,53,29,13
+75,29,13
+75,97,47,61,53
+61,13,29
+97,13,75,29,47",
+        );
+        assert_eq!(
+            result,
+            "75,97,47,61,53: move 97 to the front
+61,13,29: move 29 after 61
+97,13,75,29,47: move 47 after 75, move 13 after 29
+"
+        );
+    }
+
+    #[test]
+    fn test_partial_order() {
+        let result = process(
+            "4|1
+4|2
+6|5
+
+1,2,3,4
+5,6,7
+1,6,3,2,5,4",
+        );
+        assert_eq!(
+            result,
+            "1,2,3,4: move 4 to the front
+5,6,7: move 6 to the front
+1,6,3,2,5,4: move 4 to the front
+"
+        );
+    }
+
+    #[test]
+    fn test_repair() {
+        let mut rules = RuleGraph::new();
+        for (before, after) in [(1, 2), (2, 3), (3, 4), (4, 5)] {
+            rules.insert(before, after);
+        }
+
+        let update = [4, 5, 1, 2, 3];
+        let repair = rules.repair(&update).unwrap();
+        assert_eq!(repair.kept, vec![1, 2, 3]);
+        assert_eq!(repair.moves.len(), 2);
+        assert_eq!(repair.apply(&update), vec![1, 2, 3, 4, 5]);
+
+        let repair = rules.repair(&[5, 4, 3, 2, 1]).unwrap();
+        assert_eq!(repair.moves.len(), 4);
+        assert_eq!(repair.apply(&[5, 4, 3, 2, 1]), vec![1, 2, 3, 4, 5]);
+
+        // 4 only has to precede 1 and 2, so moving it is enough
+        let mut partial = RuleGraph::new();
+        partial.insert(4, 1);
+        partial.insert(4, 2);
+        let repair = partial.repair(&[1, 2, 3, 4]).unwrap();
+        assert_eq!(repair.kept, vec![1, 2, 3]);
+        assert_eq!(
+            repair.moves,
+            vec![Move {
+                page: 4,
+                after: None
+            }]
+        );
+        assert_eq!(repair.apply(&[1, 2, 3, 4]), vec![4, 1, 2, 3]);
+
+        // 1 and 3 can't both stay, 3 has to precede 1 through 2 even though
+        // there's no rule between them
+        let mut chained = RuleGraph::new();
+        chained.insert(3, 2);
+        chained.insert(2, 1);
+        let repair = chained.repair(&[1, 3, 2]).unwrap();
+        assert_eq!(repair.kept, vec![3, 2]);
+        assert_eq!(repair.apply(&[1, 3, 2]), vec![3, 2, 1]);
+        let repair = chained.repair(&[1, 2, 3]).unwrap();
+        assert_eq!(repair.moves.len(), 2);
+        assert_eq!(repair.apply(&[1, 2, 3]), vec![3, 2, 1]);
+
+        rules.insert(5, 1);
+        assert!(matches!(rules.repair(&update), Err(OrderError::Cycle(_))));
+    }
+}
+
+#[cfg(feature = "bench")]
+pub mod benchmarks {
+    use super::INPUT;
+
+    #[divan::bench()]
+    fn bench_process() {
+        super::process(INPUT);
+    }
+}
@@ -0,0 +1,240 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::fmt;
+
+pub type Page = u32;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OrderError {
+    DuplicatePage(Page),
+    // the pages in rule order, the last page must precede the first one again
+    Cycle(Vec<Page>),
+}
+
+impl fmt::Display for OrderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OrderError::DuplicatePage(page) => write!(f, "page {page} appears more than once"),
+            OrderError::Cycle(pages) => {
+                write!(f, "rules form a cycle")?;
+                if let Some(first) = pages.first() {
+                    write!(f, ": ")?;
+                    for page in pages {
+                        write!(f, "{page} -> ")?;
+                    }
+                    write!(f, "{first}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for OrderError {}
+
+#[derive(Debug, Default)]
+pub struct RuleGraph {
+    successors: HashMap<Page, HashSet<Page>>,
+}
+
+impl RuleGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn parse(input: &str) -> Self {
+        let mut graph = Self::new();
+        for line in input.lines() {
+            let (before, after) = line.split_once('|').expect("rule should contain `|`");
+            graph.insert(
+                before.parse().expect("should be valid"),
+                after.parse().expect("should be valid"),
+            );
+        }
+        graph
+    }
+
+    pub fn insert(&mut self, before: Page, after: Page) {
+        self.successors.entry(before).or_default().insert(after);
+    }
+
+    #[inline]
+    pub fn must_precede(&self, before: Page, after: Page) -> bool {
+        self.successors
+            .get(&before)
+            .is_some_and(|successors| successors.contains(&after))
+    }
+
+    pub fn is_ordered(&self, update: &[Page]) -> bool {
+        update.iter().enumerate().all(|(i, page)| {
+            self.successors
+                .get(page)
+                .is_none_or(|after| !update[..i].iter().any(|earlier| after.contains(earlier)))
+        })
+    }
+
+    // orders the pages of an update so that every rule between them is
+    // respected. Pages without a rule between them keep their relative order.
+    pub fn order(&self, update: &[Page]) -> Result<Vec<Page>, OrderError> {
+        let mut seen = HashSet::with_capacity(update.len());
+        if let Some(&page) = update.iter().find(|&&page| !seen.insert(page)) {
+            return Err(OrderError::DuplicatePage(page));
+        }
+
+        // rules between the pages of this update, by index into the update
+        let successors: Vec<Vec<usize>> = update
+            .iter()
+            .map(|page| match self.successors.get(page) {
+                Some(after) => (0..update.len())
+                    .filter(|&j| after.contains(&update[j]))
+                    .collect(),
+                None => vec![],
+            })
+            .collect();
+        let mut in_degree = vec![0; update.len()];
+        for &j in successors.iter().flatten() {
+            in_degree[j] += 1;
+        }
+
+        // the earliest ready page in the update goes first
+        let mut ready: BinaryHeap<Reverse<usize>> = (0..update.len())
+            .filter(|&i| in_degree[i] == 0)
+            .map(Reverse)
+            .collect();
+        let mut order = Vec::with_capacity(update.len());
+
+        while let Some(Reverse(i)) = ready.pop() {
+            order.push(update[i]);
+            for &j in &successors[i] {
+                in_degree[j] -= 1;
+                if in_degree[j] == 0 {
+                    ready.push(Reverse(j));
+                }
+            }
+        }
+
+        if order.len() == update.len() {
+            Ok(order)
+        } else {
+            let remaining: Vec<Page> = (0..update.len())
+                .filter(|&i| in_degree[i] > 0)
+                .map(|i| update[i])
+                .collect();
+            Err(OrderError::Cycle(self.cycle_within(&remaining)))
+        }
+    }
+
+    // the first adjacent pair of an ordered update that no rule decides
+    // between, which means the order isn't the only valid one
+    pub fn undecided_pair(&self, order: &[Page]) -> Option<(Page, Page)> {
+        order
+            .windows(2)
+            .find(|pair| !self.must_precede(pair[0], pair[1]))
+            .map(|pair| (pair[0], pair[1]))
+    }
+
+    // looks for a cycle over every page that appears in a rule
+    pub fn find_cycle(&self) -> Option<Vec<Page>> {
+        let mut pages: Vec<Page> = self
+            .successors
+            .iter()
+            .flat_map(|(&before, after)| std::iter::once(before).chain(after.iter().copied()))
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        pages.sort_unstable();
+
+        match self.order(&pages) {
+            Err(OrderError::Cycle(cycle)) => Some(cycle),
+            _ => None,
+        }
+    }
+
+    // every page left over by the topological sort has a predecessor that
+    // was also left over, so walking predecessors must revisit a page
+    fn cycle_within(&self, pages: &[Page]) -> Vec<Page> {
+        let mut path = vec![pages[0]];
+        loop {
+            let current = *path.last().expect("path is never empty");
+            let predecessor = *pages
+                .iter()
+                .find(|&&page| self.must_precede(page, current))
+                .expect("remaining pages should all have a predecessor");
+
+            if let Some(start) = path.iter().position(|&page| page == predecessor) {
+                let mut cycle = path.split_off(start);
+                cycle.reverse();
+                return cycle;
+            }
+            path.push(predecessor);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_order() {
+        let rules = RuleGraph::parse(
+            "47|53
+97|13
+97|61
+97|47
+75|29
+61|13
+75|53
+29|13
+97|29
+53|29
+61|53
+97|53
+61|29
+47|13
+75|47
+97|75
+47|61
+75|61
+47|29
+75|13
+53|13",
+        );
+
+        assert!(rules.is_ordered(&[75, 47, 61, 53, 29]));
+        assert!(!rules.is_ordered(&[97, 13, 75, 29, 47]));
+        assert_eq!(
+            rules.order(&[97, 13, 75, 29, 47]),
+            Ok(vec![97, 75, 47, 29, 13])
+        );
+        assert_eq!(rules.undecided_pair(&[97, 75, 47, 29, 13]), None);
+        assert_eq!(rules.find_cycle(), None);
+    }
+
+    #[test]
+    fn test_order_errors() {
+        let rules = RuleGraph::parse(
+            "100000|7
+7|123456
+123456|100000
+5|6",
+        );
+
+        assert_eq!(rules.order(&[5, 6, 5]), Err(OrderError::DuplicatePage(5)));
+        assert_eq!(rules.order(&[6, 5, 42]), Ok(vec![5, 6, 42]));
+        assert_eq!(rules.undecided_pair(&[5, 6, 42]), Some((6, 42)));
+
+        let error = rules.order(&[5, 123456, 7, 100000]).unwrap_err();
+        assert_eq!(error, OrderError::Cycle(vec![100000, 7, 123456]));
+        assert_eq!(
+            error.to_string(),
+            "rules form a cycle: 100000 -> 7 -> 123456 -> 100000"
+        );
+        // the cycle is broken when one of its pages isn't in the update
+        assert_eq!(rules.order(&[123456, 7]), Ok(vec![7, 123456]));
+
+        assert_eq!(rules.find_cycle(), Some(vec![123456, 100000, 7]));
+
+        assert_eq!(OrderError::Cycle(vec![]).to_string(), "rules form a cycle");
+    }
+}
@@ -0,0 +1,258 @@
+use grid::{Direction, Grid, Vec2};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    Started,
+    Turned,
+    Moved,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct State {
+    pub position: Vec2,
+    pub heading: Direction,
+    pub action: Action,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Exit,
+    // number of steps, turns included, before the guard is back in the same
+    // position facing the same way
+    Loop { period: usize },
+}
+
+#[derive(Debug)]
+pub struct Guard<'a> {
+    grid: &'a Grid<'a>,
+    obstacle: Option<Vec2>,
+    state: Option<State>,
+    started: bool,
+    trace: Option<Vec<State>>,
+}
+
+impl<'a> Guard<'a> {
+    pub fn new(grid: &'a Grid<'a>, start: Vec2) -> Self {
+        Self {
+            grid,
+            obstacle: None,
+            state: Some(State {
+                position: start,
+                heading: Direction::N,
+                action: Action::Started,
+            }),
+            started: false,
+            trace: None,
+        }
+    }
+
+    // places an extra obstacle on the map
+    pub fn with_obstacle(mut self, obstacle: Vec2) -> Self {
+        self.obstacle = Some(obstacle);
+        self
+    }
+
+    // records every state the guard passes through while iterating
+    pub fn with_trace(mut self) -> Self {
+        self.trace = Some(vec![]);
+        self
+    }
+
+    pub fn trace(&self) -> Option<&[State]> {
+        self.trace.as_deref()
+    }
+
+    #[inline]
+    fn step(&self, state: &State) -> Option<State> {
+        let next = &state.position + state.heading;
+        let c = self.grid.get(&next)?;
+
+        if c == '#' || self.obstacle.as_ref() == Some(&next) {
+            Some(State {
+                position: state.position.clone(),
+                heading: turn_right(state.heading),
+                action: Action::Turned,
+            })
+        } else {
+            Some(State {
+                position: next,
+                heading: state.heading,
+                action: Action::Moved,
+            })
+        }
+    }
+
+    // walks the guard from its current state until it either leaves the map
+    // or is known to be stuck in a loop. Uses Brent's cycle detection so that
+    // no visited states need to be stored.
+    pub fn run(&self) -> Outcome {
+        let Some(start) = self.state.clone() else {
+            return Outcome::Exit;
+        };
+        let same = |a: &State, b: &State| a.position == b.position && a.heading == b.heading;
+
+        let mut power = 1;
+        let mut period = 1;
+        let mut tortoise = start.clone();
+        let Some(mut hare) = self.step(&start) else {
+            return Outcome::Exit;
+        };
+
+        while !same(&tortoise, &hare) {
+            if power == period {
+                tortoise = hare.clone();
+                power *= 2;
+                period = 0;
+            }
+            let Some(next) = self.step(&hare) else {
+                return Outcome::Exit;
+            };
+            hare = next;
+            period += 1;
+        }
+
+        Outcome::Loop { period }
+    }
+
+    // draws the recorded trace onto the map, `|` and `-` for moves, `+` for
+    // turns and crossings and `O` for the extra obstacle
+    pub fn render_trace(&self) -> String {
+        let mut map: Vec<Vec<char>> = (0..self.grid.rows as i32)
+            .map(|y| {
+                (0..self.grid.cols as i32)
+                    .map(|x| self.grid.get(&Vec2::new(x, y)).unwrap_or(' '))
+                    .collect()
+            })
+            .collect();
+
+        for state in self.trace.iter().flatten() {
+            let cell = &mut map[state.position.y as usize][state.position.x as usize];
+            let line = match state.heading {
+                Direction::N | Direction::S => '|',
+                _ => '-',
+            };
+            *cell = match (state.action, *cell) {
+                (Action::Started, _) => '^',
+                (_, '^') => '^',
+                (Action::Turned, _) => '+',
+                (Action::Moved, '.') => line,
+                (Action::Moved, c) if c == line => line,
+                (Action::Moved, _) => '+',
+            };
+        }
+
+        // an obstacle off the map is never run into, so it isn't drawn either
+        if let Some(cell) = self.obstacle.as_ref().and_then(|obstacle| {
+            let row = map.get_mut(usize::try_from(obstacle.y).ok()?)?;
+            row.get_mut(usize::try_from(obstacle.x).ok()?)
+        }) {
+            *cell = 'O';
+        }
+
+        map.into_iter()
+            .map(|row| row.into_iter().collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl Iterator for Guard<'_> {
+    type Item = State;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let state = if self.started {
+            let next = self.step(self.state.as_ref()?);
+            self.state = next.clone();
+            next?
+        } else {
+            self.started = true;
+            self.state.clone()?
+        };
+
+        if let Some(trace) = &mut self.trace {
+            trace.push(state.clone());
+        }
+        Some(state)
+    }
+}
+
+#[inline]
+pub fn turn_right(heading: Direction) -> Direction {
+    match heading {
+        Direction::N => Direction::E,
+        Direction::E => Direction::S,
+        Direction::S => Direction::W,
+        Direction::W => Direction::N,
+        _ => unreachable!("guard only walks in cardinal directions"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "....#.....
+.........#
+..........
+..#.......
+.......#..
+..........
+.#..^.....
+........#.
+#.........
+......#...";
+
+    #[test]
+    fn test_outcome() {
+        let grid = Grid::new(EXAMPLE);
+        let start = Vec2::new(4, 6);
+
+        assert_eq!(Guard::new(&grid, start.clone()).run(), Outcome::Exit);
+        assert_eq!(
+            Guard::new(&grid, start.clone())
+                .with_obstacle(Vec2::new(3, 6))
+                .run(),
+            Outcome::Loop { period: 22 }
+        );
+
+        let states: Vec<State> = Guard::new(&grid, start).collect();
+        assert_eq!(
+            states.iter().filter(|s| s.action == Action::Turned).count(),
+            10
+        );
+        assert_eq!(
+            states.last().map(|s| (s.position.clone(), s.heading)),
+            Some((Vec2::new(7, 9), Direction::S))
+        );
+    }
+
+    #[test]
+    fn test_render_trace() {
+        let grid = Grid::new(EXAMPLE);
+        let mut guard = Guard::new(&grid, Vec2::new(4, 6))
+            .with_obstacle(Vec2::new(3, 6))
+            .with_trace();
+        // walk a little more than one lap of the loop
+        guard.by_ref().take(40).count();
+
+        assert_eq!(
+            guard.render_trace(),
+            "....#.....
+....+---+#
+....|...|.
+..#.|...|.
+....|..#|.
+....|...|.
+.#.O^---+.
+........#.
+#.........
+......#..."
+        );
+
+        let mut guard = Guard::new(&grid, Vec2::new(4, 6))
+            .with_obstacle(Vec2::new(-1, 20))
+            .with_trace();
+        guard.by_ref().count();
+        assert!(!guard.render_trace().contains('O'));
+    }
+}
@@ -0,0 +1,188 @@
+use grid::{Direction, Grid, Vec2};
+
+const EXIT: u32 = u32::MAX;
+
+// headings in turning order, so turning right is `(heading + 1) & 3`
+const HEADINGS: [Direction; 4] = [Direction::N, Direction::E, Direction::S, Direction::W];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Jump {
+    pub cell: u32,
+    pub heading: u8,
+}
+
+// for every cell and heading, the last cell the guard reaches before walking
+// into a wall, or `EXIT` when it walks off the map instead
+#[derive(Debug)]
+pub struct JumpTable {
+    cols: usize,
+    rows: usize,
+    stops: Vec<[u32; 4]>,
+}
+
+impl JumpTable {
+    pub fn new(grid: &Grid) -> Self {
+        let (cols, rows) = (grid.cols, grid.rows);
+        let wall = |x: usize, y: usize| grid.get(&Vec2::new(x as i32, y as i32)) == Some('#');
+        let mut stops = vec![[EXIT; 4]; cols * rows];
+        if stops.is_empty() {
+            return Self { cols, rows, stops };
+        }
+
+        // each direction is filled in starting from the edge it points to, so
+        // the neighbour's stop is always known
+        for x in 0..cols {
+            for y in 1..rows {
+                let i = y * cols + x;
+                stops[i][0] = if wall(x, y - 1) {
+                    i as u32
+                } else {
+                    stops[i - cols][0]
+                };
+            }
+            for y in (0..rows - 1).rev() {
+                let i = y * cols + x;
+                stops[i][2] = if wall(x, y + 1) {
+                    i as u32
+                } else {
+                    stops[i + cols][2]
+                };
+            }
+        }
+        for y in 0..rows {
+            for x in (0..cols - 1).rev() {
+                let i = y * cols + x;
+                stops[i][1] = if wall(x + 1, y) {
+                    i as u32
+                } else {
+                    stops[i + 1][1]
+                };
+            }
+            for x in 1..cols {
+                let i = y * cols + x;
+                stops[i][3] = if wall(x - 1, y) {
+                    i as u32
+                } else {
+                    stops[i - 1][3]
+                };
+            }
+        }
+
+        Self { cols, rows, stops }
+    }
+
+    #[inline]
+    pub fn cell(&self, point: &Vec2) -> u32 {
+        debug_assert!((point.x as usize) < self.cols && (point.y as usize) < self.rows);
+        (point.y as usize * self.cols + point.x as usize) as u32
+    }
+
+    #[inline]
+    pub fn heading(direction: Direction) -> u8 {
+        HEADINGS
+            .iter()
+            .position(|&d| d == direction)
+            .expect("guard only walks in cardinal directions") as u8
+    }
+
+    // walks from `from` until the next wall, or the extra obstacle if it is
+    // in the way, and turns right. The table itself is never modified, the
+    // obstacle only shortens the jump that crosses it.
+    #[inline]
+    pub fn jump(&self, from: Jump, obstacle: u32) -> Option<Jump> {
+        let stop = self.stops[from.cell as usize][from.heading as usize];
+        let (cols, cell) = (self.cols as u32, from.cell);
+        let (x, y) = (cell % cols, cell / cols);
+        let (ox, oy) = (obstacle % cols, obstacle / cols);
+
+        // cell in front of the obstacle when it lies between here and the stop
+        let blocked = match from.heading {
+            0 if ox == x && oy < y && (stop == EXIT || oy >= stop / cols) => Some(obstacle + cols),
+            1 if oy == y && ox > x && (stop == EXIT || ox <= stop % cols) => Some(obstacle - 1),
+            2 if ox == x && oy > y && (stop == EXIT || oy <= stop / cols) => Some(obstacle - cols),
+            3 if oy == y && ox < x && (stop == EXIT || ox >= stop % cols) => Some(obstacle + 1),
+            _ => None,
+        };
+
+        let cell = match blocked {
+            Some(cell) => cell,
+            None if stop == EXIT => return None,
+            None => stop,
+        };
+
+        Some(Jump {
+            cell,
+            heading: (from.heading + 1) & 3,
+        })
+    }
+
+    // same as `Guard::run` but moving from wall to wall, returns whether the
+    // guard ends up walking in a loop
+    pub fn loops(&self, start: Jump, obstacle: u32) -> bool {
+        let mut power = 1;
+        let mut period = 1;
+        let mut tortoise = start;
+        let Some(mut hare) = self.jump(start, obstacle) else {
+            return false;
+        };
+
+        while tortoise != hare {
+            if power == period {
+                tortoise = hare;
+                power *= 2;
+                period = 0;
+            }
+            let Some(next) = self.jump(hare, obstacle) else {
+                return false;
+            };
+            hare = next;
+            period += 1;
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::guard::{Guard, Outcome};
+
+    #[test]
+    fn test_matches_guard() {
+        let input = "....#.....
+.........#
+..........
+..#.......
+.......#..
+..........
+.#..^.....
+........#.
+#.........
+......#...";
+        let grid = Grid::new(input);
+        let table = JumpTable::new(&grid);
+        let start = Vec2::new(4, 6);
+        let from = Jump {
+            cell: table.cell(&start),
+            heading: 0,
+        };
+
+        for y in 0..grid.rows as i32 {
+            for x in 0..grid.cols as i32 {
+                let obstacle = Vec2::new(x, y);
+                if obstacle == start || grid.get(&obstacle) == Some('#') {
+                    continue;
+                }
+                let expected = Guard::new(&grid, start.clone())
+                    .with_obstacle(obstacle.clone())
+                    .run();
+                assert_eq!(
+                    table.loops(from, table.cell(&obstacle)),
+                    matches!(expected, Outcome::Loop { .. }),
+                    "obstacle at {obstacle:?}"
+                );
+            }
+        }
+    }
+}
@@ -1,2 +1,5 @@
+pub mod guard;
+pub mod jump;
 pub mod part1;
 pub mod part2;
+pub mod part2_stepping;
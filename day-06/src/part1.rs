@@ -1,5 +1,5 @@
-use grid::{Direction, Grid};
-use std::ops::Add;
+use crate::guard::Guard;
+use grid::Grid;
 
 const INPUT: &str = include_str!("input.txt");
 
@@ -10,38 +10,15 @@ pub fn run() -> String {
 
 #[tracing::instrument(level = "trace", skip(input))]
 fn process(input: &str) -> u32 {
-    let get_direction = |i: usize| match i {
-        x if x % 4 == 0 => Direction::N,
-        x if x % 4 == 1 => Direction::E,
-        x if x % 4 == 2 => Direction::S,
-        x if x % 4 == 3 => Direction::W,
-        _ => unreachable!(),
-    };
-
     let grid = Grid::new(input);
     let mut map: Vec<u8> = Vec::with_capacity(grid.cols * grid.rows);
     map.extend(input.lines().flat_map(|line| line.chars().map(|_| 0)));
 
-    let mut current = grid.index_to_vec2(input.find('^').expect("start point should exist"));
-    // Direction index
-    let mut i = 0;
+    let start = grid.index_to_vec2(input.find('^').expect("start point should exist"));
 
-    loop {
+    for state in Guard::new(&grid, start) {
         // update map
-        map[current.x as usize + current.y as usize * grid.cols] = 1;
-
-        // step
-        let next = current.add(get_direction(i));
-        let Some(c) = grid.get(&next) else {
-            break;
-        };
-
-        if c == '#' {
-            // turn
-            i += 1;
-        } else {
-            current = next;
-        }
+        map[state.position.x as usize + state.position.y as usize * grid.cols] = 1;
     }
 
     map.iter().fold(0, |total, x| total + *x as u32)
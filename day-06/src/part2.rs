@@ -1,6 +1,7 @@
+use crate::guard::{Action, Guard};
+use crate::jump::{Jump, JumpTable};
 use grid::{Direction, Grid};
-use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
-use std::{collections::BTreeSet, ops::Add};
+use rayon::iter::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
 
 const INPUT: &str = include_str!("input.txt");
 
@@ -11,117 +12,41 @@ pub fn run() -> String {
 
 #[tracing::instrument(level = "trace", skip(input))]
 fn process(input: &str) -> u32 {
-    let get_direction = |i: u8| match i {
-        x if x % 4 == 0 => Direction::N,
-        x if x % 4 == 1 => Direction::E,
-        x if x % 4 == 2 => Direction::S,
-        x if x % 4 == 3 => Direction::W,
-        _ => unreachable!(),
-    };
-
     let grid = Grid::new(input);
 
     let start_index = input.find('^').expect("start point should exist");
 
     let start = grid.index_to_vec2(start_index);
 
-    let mut stepper = start.clone();
-    let mut stepper_direction = 0;
+    let table = JumpTable::new(&grid);
 
-    let mut visited: BTreeSet<u32> = BTreeSet::new();
-
-    loop {
-        // step
-        let next = stepper.add(get_direction(stepper_direction));
-        let Some(c) = grid.get(&next) else {
-            break;
+    // an obstacle is first run into from the state just before the guard
+    // would have entered its cell, so the walk can resume from there
+    let mut first_entry: Vec<Option<Jump>> = vec![None; grid.cols * grid.rows];
+    let mut previous = Jump {
+        cell: table.cell(&start),
+        heading: JumpTable::heading(Direction::N),
+    };
+    for state in Guard::new(&grid, start.clone()) {
+        let current = Jump {
+            cell: table.cell(&state.position),
+            heading: JumpTable::heading(state.heading),
         };
-
-        if c == '#' {
-            // turn
-            stepper_direction += 1;
-            stepper_direction &= 3;
-        } else {
-            // update map
-            visited.insert(grid.point_to_index(&next).expect("point should be valid") as u32);
-            stepper = next;
+        // no obstacle can be placed on the start
+        if state.action == Action::Moved
+            && state.position != start
+            && first_entry[current.cell as usize].is_none()
+        {
+            first_entry[current.cell as usize] = Some(previous);
         }
+        previous = current;
     }
 
-    visited
+    first_entry
         .par_iter()
-        .map(|&obstacle_index| {
-            let obstacle = grid.index_to_vec2(obstacle_index as usize);
-
-            // Resume from previous fork or start at the beginning
-            let mut double_stepper = start.clone();
-            let mut single_stepper = start.clone();
-            // Direction index
-            let mut double_stepper_direction = 0;
-            let mut single_stepper_direction = 0;
-
-            // loop detection makes use of 2 walkers following the path. The one
-            // walks twice as fast as the other. If they meet on the same spot and
-            // are facing the same direction, we've hit a loop. If the fast walker
-            // leaves the map, there is no loop.
-            loop {
-                // double stepper step 1
-                {
-                    let next = double_stepper.add(get_direction(double_stepper_direction));
-                    let Some(c) = grid.get(&next) else {
-                        return 0;
-                    };
-
-                    if c == '#' || obstacle == next {
-                        // turn
-                        double_stepper_direction += 1;
-                        double_stepper_direction &= 3;
-                    } else {
-                        double_stepper = next;
-                    }
-                }
-
-                // double stepper step 2
-                {
-                    let next = double_stepper.add(get_direction(double_stepper_direction));
-                    let Some(c) = grid.get(&next) else {
-                        return 0;
-                    };
-
-                    if c == '#' || obstacle == next {
-                        // turn
-                        double_stepper_direction += 1;
-                        double_stepper_direction &= 3;
-                    } else {
-                        double_stepper = next;
-                    }
-                }
-
-                // single stepper
-                {
-                    let next = single_stepper.add(get_direction(single_stepper_direction));
-                    let Some(c) = grid.get(&next) else {
-                        return 0;
-                    };
-
-                    if c == '#' || obstacle == next {
-                        // turn
-                        single_stepper_direction += 1;
-                        single_stepper_direction &= 3;
-                    } else {
-                        single_stepper = next;
-                    }
-                }
-
-                // has double stepper caught up with single stepper?
-                if single_stepper == double_stepper
-                    && single_stepper_direction == double_stepper_direction
-                {
-                    return 1;
-                }
-            }
-        })
-        .sum()
+        .enumerate()
+        .filter(|(obstacle, from)| from.is_some_and(|from| table.loops(from, *obstacle as u32)))
+        .count() as u32
 }
 
 #[cfg(test)]
@@ -144,6 +69,60 @@ mod tests {
         );
         assert_eq!(result, 6);
     }
+
+    #[test]
+    fn test_start_is_not_an_obstacle() {
+        // the guard walks back through the start, an obstacle there would
+        // make a third loop
+        let result = process(
+            "..#.#
+.#.#.
+..^..
+#....
+..#..",
+        );
+        assert_eq!(result, 2);
+    }
+
+    #[test]
+    fn test_matches_stepping() {
+        use crate::guard::Outcome;
+        use grid::Vec2;
+
+        let mut state: u64 = 2024;
+        let mut checked = 0;
+        while checked < 20 {
+            let mut input = String::new();
+            for y in 0..40 {
+                for x in 0..40 {
+                    // xorshift64
+                    state ^= state << 13;
+                    state ^= state >> 7;
+                    state ^= state << 17;
+                    input.push(match (x, y) {
+                        (20, 30) => '^',
+                        _ if state % 100 < 6 => '#',
+                        _ => '.',
+                    });
+                }
+                if y < 39 {
+                    input.push('\n');
+                }
+            }
+
+            // both walk the guard's original path, so it has to leave the map
+            let grid = Grid::new(&input);
+            if Guard::new(&grid, Vec2::new(20, 30)).run() != Outcome::Exit {
+                continue;
+            }
+            assert_eq!(
+                process(&input),
+                crate::part2_stepping::process(&input),
+                "{input}"
+            );
+            checked += 1;
+        }
+    }
 }
 
 #[cfg(feature = "bench")]
@@ -0,0 +1,81 @@
+use crate::guard::{Action, Guard, Outcome};
+use grid::Grid;
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use std::collections::BTreeSet;
+
+const INPUT: &str = include_str!("input.txt");
+
+#[tracing::instrument(level = "trace", skip())]
+pub fn run() -> String {
+    process(INPUT).to_string()
+}
+
+#[tracing::instrument(level = "trace", skip(input))]
+pub(crate) fn process(input: &str) -> u32 {
+    let grid = Grid::new(input);
+
+    let start_index = input.find('^').expect("start point should exist");
+
+    let start = grid.index_to_vec2(start_index);
+
+    let visited: BTreeSet<u32> = Guard::new(&grid, start.clone())
+        // no obstacle can be placed on the start, even when the guard walks
+        // back through it
+        .filter(|state| state.action == Action::Moved && state.position != start)
+        .map(|state| {
+            grid.point_to_index(&state.position)
+                .expect("point should be valid") as u32
+        })
+        .collect();
+
+    visited
+        .par_iter()
+        .map(|&obstacle_index| {
+            let obstacle = grid.index_to_vec2(obstacle_index as usize);
+
+            match Guard::new(&grid, start.clone())
+                .with_obstacle(obstacle)
+                .run()
+            {
+                Outcome::Loop { .. } => 1,
+                Outcome::Exit => 0,
+            }
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_works() {
+        let result = process(
+            "....#.....
+.........#
+..........
+..#.......
+.......#..
+..........
+.#..^.....
+........#.
+#.........
+......#...",
+        );
+        assert_eq!(result, 6);
+    }
+}
+
+#[cfg(feature = "bench")]
+pub mod benchmarks {
+    use super::INPUT;
+
+    pub fn main() {
+        divan::main();
+    }
+
+    #[divan::bench()]
+    fn bench_process() {
+        super::process(INPUT);
+    }
+}
@@ -0,0 +1,16 @@
+// prints how each calibration is solved, with operators picked by symbol
+// like `cargo run -p day-07 --bin solve -- '+,*,||16'`
+fn main() {
+    let operators = std::env::args().nth(1);
+    let operators = operators
+        .as_deref()
+        .unwrap_or(day_07::solver::DEFAULT_OPERATORS);
+
+    match day_07::solver::run_with(operators) {
+        Ok(result) => print!("{}", result),
+        Err(error) => {
+            eprintln!("{error}");
+            std::process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,378 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::sync::Arc;
+
+// a result, or left operand, that doesn't fit in a u64
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Overflow;
+
+impl fmt::Display for Overflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "doesn't fit in a u64")
+    }
+}
+
+impl std::error::Error for Overflow {}
+
+pub trait Operator: fmt::Debug + Send + Sync {
+    fn symbol(&self) -> &str;
+
+    // `None` when the operator isn't defined for these operands, like
+    // dividing by zero
+    fn apply(&self, lhs: u64, rhs: u64) -> Result<Option<u64>, Overflow>;
+
+    // every left operand that gives `total` when combined with `rhs`, used to
+    // search backwards from the total. `None` when there isn't one.
+    fn inverse(&self, total: u64, rhs: u64) -> Result<Option<RangeInclusive<u64>>, Overflow>;
+}
+
+#[inline]
+fn exactly(lhs: u64) -> Option<RangeInclusive<u64>> {
+    Some(lhs..=lhs)
+}
+
+#[derive(Debug)]
+pub struct Add;
+
+impl Operator for Add {
+    fn symbol(&self) -> &str {
+        "+"
+    }
+
+    fn apply(&self, lhs: u64, rhs: u64) -> Result<Option<u64>, Overflow> {
+        lhs.checked_add(rhs).map(Some).ok_or(Overflow)
+    }
+
+    fn inverse(&self, total: u64, rhs: u64) -> Result<Option<RangeInclusive<u64>>, Overflow> {
+        Ok(total.checked_sub(rhs).and_then(exactly))
+    }
+}
+
+// isn't defined below zero, the same as adding a number that's too large
+// isn't defined in reverse
+#[derive(Debug)]
+pub struct Sub;
+
+impl Operator for Sub {
+    fn symbol(&self) -> &str {
+        "-"
+    }
+
+    fn apply(&self, lhs: u64, rhs: u64) -> Result<Option<u64>, Overflow> {
+        Ok(lhs.checked_sub(rhs))
+    }
+
+    fn inverse(&self, total: u64, rhs: u64) -> Result<Option<RangeInclusive<u64>>, Overflow> {
+        total.checked_add(rhs).map(exactly).ok_or(Overflow)
+    }
+}
+
+#[derive(Debug)]
+pub struct Mul;
+
+impl Operator for Mul {
+    fn symbol(&self) -> &str {
+        "*"
+    }
+
+    fn apply(&self, lhs: u64, rhs: u64) -> Result<Option<u64>, Overflow> {
+        lhs.checked_mul(rhs).map(Some).ok_or(Overflow)
+    }
+
+    fn inverse(&self, total: u64, rhs: u64) -> Result<Option<RangeInclusive<u64>>, Overflow> {
+        Ok(match (total, rhs) {
+            // anything times zero is zero
+            (0, 0) => Some(0..=u64::MAX),
+            (_, 0) => None,
+            _ => (total % rhs == 0).then(|| total / rhs).and_then(exactly),
+        })
+    }
+}
+
+// integer division rounding down
+#[derive(Debug)]
+pub struct Div;
+
+impl Operator for Div {
+    fn symbol(&self) -> &str {
+        "/"
+    }
+
+    fn apply(&self, lhs: u64, rhs: u64) -> Result<Option<u64>, Overflow> {
+        Ok(lhs.checked_div(rhs))
+    }
+
+    fn inverse(&self, total: u64, rhs: u64) -> Result<Option<RangeInclusive<u64>>, Overflow> {
+        if rhs == 0 {
+            return Ok(None);
+        }
+        // every left operand that rounds down to the total, the ones past
+        // u64::MAX can't be reached anyway
+        let lowest = total.checked_mul(rhs).ok_or(Overflow)?;
+        Ok(Some(lowest..=lowest.saturating_add(rhs - 1)))
+    }
+}
+
+#[derive(Debug)]
+pub struct Xor;
+
+impl Operator for Xor {
+    fn symbol(&self) -> &str {
+        "^"
+    }
+
+    fn apply(&self, lhs: u64, rhs: u64) -> Result<Option<u64>, Overflow> {
+        Ok(Some(lhs ^ rhs))
+    }
+
+    fn inverse(&self, total: u64, rhs: u64) -> Result<Option<RangeInclusive<u64>>, Overflow> {
+        Ok(exactly(total ^ rhs))
+    }
+}
+
+// raises the left operand to the power of the right one, `0 ** 0` is 1
+#[derive(Debug)]
+pub struct Pow;
+
+impl Operator for Pow {
+    fn symbol(&self) -> &str {
+        "**"
+    }
+
+    fn apply(&self, lhs: u64, rhs: u64) -> Result<Option<u64>, Overflow> {
+        match (lhs, rhs) {
+            (_, 0) => Ok(Some(1)),
+            (0 | 1, _) => Ok(Some(lhs)),
+            _ => u32::try_from(rhs)
+                .ok()
+                .and_then(|rhs| lhs.checked_pow(rhs))
+                .map(Some)
+                .ok_or(Overflow),
+        }
+    }
+
+    fn inverse(&self, total: u64, rhs: u64) -> Result<Option<RangeInclusive<u64>>, Overflow> {
+        if rhs == 0 {
+            return Ok((total == 1).then_some(0..=u64::MAX));
+        }
+        if total <= 1 {
+            return Ok(exactly(total));
+        }
+
+        // the largest root whose power doesn't pass the total
+        let (mut low, mut high) = (1, total);
+        while low < high {
+            let middle = low + (high - low).div_ceil(2);
+            match self.apply(middle, rhs) {
+                Ok(Some(power)) if power <= total => low = middle,
+                _ => high = middle - 1,
+            }
+        }
+        Ok((self.apply(low, rhs) == Ok(Some(total)))
+            .then_some(low)
+            .and_then(exactly))
+    }
+}
+
+// writes the digits of the right operand after the left one, in any base
+// from 2 to 36
+#[derive(Debug)]
+pub struct Concatenate {
+    base: u64,
+    symbol: String,
+}
+
+impl Concatenate {
+    pub fn new(base: u64) -> Self {
+        assert!((2..=36).contains(&base), "base should be from 2 to 36");
+        let symbol = match base {
+            10 => "||".to_string(),
+            _ => format!("||{base}"),
+        };
+        Self { base, symbol }
+    }
+
+    // the power of the base that makes room for the digits of `rhs`, `None`
+    // when it doesn't fit in a u64
+    #[inline]
+    fn shift(&self, rhs: u64) -> Option<u64> {
+        self.base
+            .checked_pow(rhs.checked_ilog(self.base).unwrap_or(0) + 1)
+    }
+}
+
+impl Operator for Concatenate {
+    fn symbol(&self) -> &str {
+        &self.symbol
+    }
+
+    fn apply(&self, lhs: u64, rhs: u64) -> Result<Option<u64>, Overflow> {
+        if lhs == 0 {
+            return Ok(Some(rhs));
+        }
+        self.shift(rhs)
+            .and_then(|shift| lhs.checked_mul(shift))
+            .and_then(|shifted| shifted.checked_add(rhs))
+            .map(Some)
+            .ok_or(Overflow)
+    }
+
+    fn inverse(&self, total: u64, rhs: u64) -> Result<Option<RangeInclusive<u64>>, Overflow> {
+        Ok(match self.shift(rhs) {
+            Some(shift) => (total % shift == rhs)
+                .then(|| total / shift)
+                .and_then(exactly),
+            // only a left operand of zero adds no digits
+            None => (total == rhs).then_some(0..=0),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownOperator(pub String);
+
+impl fmt::Display for UnknownOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown operator `{}`", self.0)
+    }
+}
+
+impl std::error::Error for UnknownOperator {}
+
+// operators by symbol, the default registry has every operator above with
+// concatenation in bases 2 to 36 as `||2` to `||36`, and `||` for base 10
+#[derive(Debug, Clone)]
+pub struct Registry {
+    operators: HashMap<String, Arc<dyn Operator>>,
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        let mut registry = Self::empty();
+        registry.register(Add);
+        registry.register(Sub);
+        registry.register(Mul);
+        registry.register(Div);
+        registry.register(Xor);
+        registry.register(Pow);
+        for base in 2..=36 {
+            registry.register(Concatenate::new(base));
+        }
+        registry
+    }
+}
+
+impl Registry {
+    pub fn empty() -> Self {
+        Self {
+            operators: HashMap::new(),
+        }
+    }
+
+    // adds an operator under its symbol, replacing any with the same symbol
+    pub fn register(&mut self, operator: impl Operator + 'static) {
+        self.operators
+            .insert(operator.symbol().to_string(), Arc::new(operator));
+    }
+
+    pub fn get(&self, symbol: &str) -> Option<Arc<dyn Operator>> {
+        self.operators.get(symbol).cloned()
+    }
+
+    // picks operators by a comma separated list of symbols, `+,*,||`
+    pub fn select(&self, symbols: &str) -> Result<Vec<Arc<dyn Operator>>, UnknownOperator> {
+        symbols
+            .split(',')
+            .map(str::trim)
+            .filter(|symbol| !symbol.is_empty())
+            .map(|symbol| {
+                self.get(symbol)
+                    .ok_or_else(|| UnknownOperator(symbol.to_string()))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checked() {
+        assert_eq!(Add.apply(u64::MAX, 1), Err(Overflow));
+        assert_eq!(Sub.apply(1, 2), Ok(None));
+        assert_eq!(Mul.apply(u64::MAX, 2), Err(Overflow));
+        assert_eq!(Div.apply(7, 0), Ok(None));
+        assert_eq!(Pow.apply(2, 64), Err(Overflow));
+        assert_eq!(Pow.apply(1, u64::MAX), Ok(Some(1)));
+        assert_eq!(Concatenate::new(10).apply(12, 345), Ok(Some(12345)));
+        assert_eq!(Concatenate::new(10).apply(12, 0), Ok(Some(120)));
+        assert_eq!(Concatenate::new(2).apply(0b101, 0b11), Ok(Some(0b10111)));
+        assert_eq!(Concatenate::new(10).apply(u64::MAX / 10, 99), Err(Overflow));
+    }
+
+    #[test]
+    fn test_inverse() {
+        let registry = Registry::default();
+        let operators = registry.select("+,-,*,/,^,**,||,||2,||16").unwrap();
+
+        // every inverse holds exactly the left operands that give the total
+        for operator in &operators {
+            for total in 0..100 {
+                for rhs in 0..8 {
+                    let expected: Vec<u64> = (0..1000)
+                        .filter(|&lhs| operator.apply(lhs, rhs) == Ok(Some(total)))
+                        .collect();
+                    let found: Vec<u64> = match operator.inverse(total, rhs).unwrap() {
+                        Some(range) => range.take_while(|&lhs| lhs < 1000).collect(),
+                        None => vec![],
+                    };
+                    assert_eq!(found, expected, "{total} = lhs {} {rhs}", operator.symbol());
+                }
+            }
+        }
+
+        assert_eq!(Sub.inverse(u64::MAX, 1), Err(Overflow));
+        assert_eq!(Div.inverse(u64::MAX, 2), Err(Overflow));
+    }
+
+    #[test]
+    fn test_registry() {
+        let registry = Registry::default();
+        assert_eq!(
+            registry.get("||16").unwrap().apply(0xab, 0xc),
+            Ok(Some(0xabc))
+        );
+        assert_eq!(
+            registry.select("+, *,%").unwrap_err(),
+            UnknownOperator("%".to_string())
+        );
+
+        #[derive(Debug)]
+        struct Max;
+        impl Operator for Max {
+            fn symbol(&self) -> &str {
+                "max"
+            }
+            fn apply(&self, lhs: u64, rhs: u64) -> Result<Option<u64>, Overflow> {
+                Ok(Some(lhs.max(rhs)))
+            }
+            fn inverse(
+                &self,
+                total: u64,
+                rhs: u64,
+            ) -> Result<Option<RangeInclusive<u64>>, Overflow> {
+                Ok(match total.cmp(&rhs) {
+                    std::cmp::Ordering::Less => None,
+                    std::cmp::Ordering::Equal => Some(0..=total),
+                    std::cmp::Ordering::Greater => exactly(total),
+                })
+            }
+        }
+
+        let mut registry = Registry::empty();
+        registry.register(Max);
+        assert_eq!(registry.select("max").unwrap()[0].apply(3, 5), Ok(Some(5)));
+    }
+}
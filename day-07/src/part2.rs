@@ -1,4 +1,5 @@
-use itertools::Itertools;
+use crate::operator::Registry;
+use crate::solver::Equation;
 use rayon::prelude::*;
 
 const INPUT: &str = include_str!("input.txt");
@@ -8,86 +9,31 @@ pub fn run() -> String {
     process(INPUT).to_string()
 }
 
-#[derive(Debug, Clone)]
-enum Operator {
-    Add,
-    Mul,
-    Concatenate,
-}
-
-pub trait Concatenate<Rhs = Self> {
-    type Output;
-
-    #[must_use = "this returns the result of the operation, without modifying the original"]
-    fn concatenate(self, rhs: Rhs) -> Self::Output;
-}
-
-impl Concatenate<u64> for u64 {
-    type Output = u64;
-
-    #[inline]
-    fn concatenate(self, rhs: u64) -> Self::Output {
-        self * 10_i32.pow(rhs.ilog10() + 1) as u64 + rhs
-    }
-}
-
-const OPERATORS: [Operator; 3] = [Operator::Add, Operator::Mul, Operator::Concatenate];
-
 #[tracing::instrument(level = "trace", skip(input))]
 pub fn process(input: &str) -> u64 {
-    let total = parse(input)
-        .par_bridge()
-        .map(|(total, operands)| {
-            (0..operands.len() - 1)
-                .map(|_| OPERATORS)
-                .multi_cartesian_product()
-                .any(|operator_sequence| {
-                    let mut operators = operator_sequence.iter();
-                    let computed_total = operands
-                        .iter()
-                        .copied()
-                        .reduce(|total, operand| match operators.next() {
-                            Some(Operator::Add) => total + operand,
-                            Some(Operator::Mul) => total * operand,
-                            Some(Operator::Concatenate) => total.concatenate(operand),
-                            _ => unreachable!("there should be an operator"),
-                        })
-                        .unwrap_or(0);
+    let operators = Registry::default()
+        .select("+,*,||")
+        .expect("operators should exist");
 
-                    computed_total == total
-                })
-                .then_some(total)
-                .unwrap_or_default()
+    let total = input
+        .lines()
+        .filter_map(Equation::parse)
+        .par_bridge()
+        .filter(|equation| {
+            equation
+                .is_solvable(&operators)
+                .unwrap_or_else(|error| panic!("{equation:?} has no solution that {error}"))
         })
+        .map(|equation| equation.total)
         .sum();
 
     total
 }
 
-#[inline]
-fn parse(input: &str) -> impl Iterator<Item = (u64, std::vec::Vec<u64>)> + use<'_> {
-    input.lines().filter_map(|line| {
-        line.split_once(": ").map(|(total, operands)| {
-            let total = total.parse::<u64>().expect("there should be a total");
-
-            let operands = operands
-                .split(' ')
-                .map(|operand| operand.parse::<u64>().expect("operand should be valid"))
-                .collect::<Vec<u64>>();
-            (total, operands)
-        })
-    })
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_concatenate() {
-        assert_eq!(12_u64.concatenate(345), 12345);
-    }
-
     #[test]
     fn it_works() {
         let result = process(
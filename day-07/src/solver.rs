@@ -0,0 +1,367 @@
+use std::ops::RangeInclusive;
+use std::sync::Arc;
+
+use crate::operator::{Operator, Overflow, Registry, UnknownOperator};
+
+const INPUT: &str = include_str!("input.txt");
+
+pub const DEFAULT_OPERATORS: &str = "+,*,||";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Equation {
+    pub total: u64,
+    pub operands: Vec<u64>,
+}
+
+impl Equation {
+    pub fn parse(line: &str) -> Option<Self> {
+        let (total, operands) = line.split_once(": ")?;
+        let total = total.parse::<u64>().expect("there should be a total");
+        let operands = operands
+            .split(' ')
+            .map(|operand| operand.parse::<u64>().expect("operand should be valid"))
+            .collect();
+
+        Some(Self { total, operands })
+    }
+
+    // finds operators that make the operands add up to the total, trying them
+    // in the given order from the last operand to the first. Fails with
+    // `Overflow` when there's no solution within a u64 but some operators
+    // would have needed a larger value, so there might be one.
+    pub fn solve(
+        &self,
+        operators: &[Arc<dyn Operator>],
+    ) -> Result<Option<Vec<Arc<dyn Operator>>>, Overflow> {
+        let mut search = Search {
+            operators,
+            solution: Vec::with_capacity(self.operands.len().saturating_sub(1)),
+            overflowed: false,
+        };
+
+        if search.backward(self.total..=self.total, &self.operands) {
+            Ok(Some(
+                search
+                    .solution
+                    .iter()
+                    .map(|&i| operators[i].clone())
+                    .collect(),
+            ))
+        } else if search.overflowed {
+            Err(Overflow)
+        } else {
+            Ok(None)
+        }
+    }
+
+    pub fn is_solvable(&self, operators: &[Arc<dyn Operator>]) -> Result<bool, Overflow> {
+        self.solve(operators).map(|solution| solution.is_some())
+    }
+
+    // `None` when an operator isn't defined for its operands
+    pub fn evaluate(&self, operators: &[Arc<dyn Operator>]) -> Result<Option<u64>, Overflow> {
+        assert_eq!(
+            operators.len() + 1,
+            self.operands.len(),
+            "there should be an operator between each pair of operands"
+        );
+        let Some((&first, rest)) = self.operands.split_first() else {
+            return Ok(None);
+        };
+
+        let mut total = first;
+        for (operator, &operand) in operators.iter().zip(rest) {
+            match operator.apply(total, operand)? {
+                Some(result) => total = result,
+                None => return Ok(None),
+            }
+        }
+        Ok(Some(total))
+    }
+
+    // writes the equation out with its operators, `3267 = 81 + 40 * 27`
+    pub fn display(&self, operators: &[Arc<dyn Operator>]) -> String {
+        let mut result = format!("{} =", self.total);
+        for (i, operand) in self.operands.iter().enumerate() {
+            if let Some(operator) = i.checked_sub(1).map(|i| &operators[i]) {
+                result.push_str(&format!(" {}", operator.symbol()));
+            }
+            result.push_str(&format!(" {operand}"));
+        }
+        result
+    }
+}
+
+struct Search<'a> {
+    operators: &'a [Arc<dyn Operator>],
+    // indices into `operators`
+    solution: Vec<usize>,
+    overflowed: bool,
+}
+
+impl Search<'_> {
+    // pushes the operators between `operands` onto the solution when they can
+    // make any of `totals`
+    fn backward(&mut self, totals: RangeInclusive<u64>, operands: &[u64]) -> bool {
+        let Some((&last, init)) = operands.split_last() else {
+            return false;
+        };
+        if init.is_empty() {
+            return totals.contains(&last);
+        }
+
+        // trying every total is slower than trying every operator from the
+        // front once there are more totals than operator sequences, like
+        // after dividing by a large number
+        let sequences = (self.operators.len() as u64).saturating_pow(init.len() as u32);
+        if totals.end() - totals.start() >= sequences {
+            return self.forward(operands[0], &operands[1..], &totals);
+        }
+
+        for total in totals {
+            for (i, operator) in self.operators.iter().enumerate() {
+                match operator.inverse(total, last) {
+                    Ok(Some(lhs)) => {
+                        if self.backward(lhs, init) {
+                            self.solution.push(i);
+                            return true;
+                        }
+                    }
+                    Ok(None) => {}
+                    Err(Overflow) => self.overflowed = true,
+                }
+            }
+        }
+
+        false
+    }
+
+    // pushes the operators that turn `value` followed by `operands` into any
+    // of `totals` onto the solution
+    fn forward(&mut self, value: u64, operands: &[u64], totals: &RangeInclusive<u64>) -> bool {
+        let Some((&next, rest)) = operands.split_first() else {
+            return totals.contains(&value);
+        };
+
+        for (i, operator) in self.operators.iter().enumerate() {
+            match operator.apply(value, next) {
+                Ok(Some(value)) => {
+                    self.solution.push(i);
+                    if self.forward(value, rest, totals) {
+                        return true;
+                    }
+                    self.solution.pop();
+                }
+                Ok(None) => {}
+                Err(Overflow) => self.overflowed = true,
+            }
+        }
+
+        false
+    }
+}
+
+#[tracing::instrument(level = "trace", skip())]
+pub fn run() -> String {
+    process(INPUT, DEFAULT_OPERATORS).expect("default operators should exist")
+}
+
+// solves the input with a comma separated list of operator symbols, see
+// `Registry` for the ones that are known
+#[tracing::instrument(level = "trace", skip())]
+pub fn run_with(operators: &str) -> Result<String, UnknownOperator> {
+    process(INPUT, operators)
+}
+
+#[tracing::instrument(level = "trace", skip(input))]
+fn process(input: &str, operators: &str) -> Result<String, UnknownOperator> {
+    let operators = Registry::default().select(operators)?;
+
+    let mut report = String::new();
+    let mut sum = Some(0_u64);
+    for equation in input.lines().filter_map(Equation::parse) {
+        let operands = equation
+            .operands
+            .iter()
+            .map(u64::to_string)
+            .collect::<Vec<_>>()
+            .join(" ");
+        match equation.solve(&operators) {
+            Ok(Some(solution)) => {
+                report.push_str(&equation.display(&solution));
+                sum = sum.and_then(|sum| sum.checked_add(equation.total));
+            }
+            Ok(None) => report.push_str(&format!("{}: {operands} has no solution", equation.total)),
+            Err(error) => report.push_str(&format!(
+                "{}: {operands} has no solution that {error}",
+                equation.total
+            )),
+        }
+        report.push('\n');
+    }
+    match sum {
+        Some(sum) => report.push_str(&format!("total: {sum}\n")),
+        None => report.push_str(&format!("total: {Overflow}\n")),
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use itertools::Itertools;
+
+    const EXAMPLE: &str = "190: 10 19
+3267: 81 40 27
+83: 17 5
+156: 15 6
+7290: 6 8 6 15
+161011: 16 10 13
+192: 17 8 14
+21037: 9 7 18 13
+292: 11 6 16 20";
+
+    fn symbols(solution: &[Arc<dyn Operator>]) -> Vec<&str> {
+        solution.iter().map(|operator| operator.symbol()).collect()
+    }
+
+    #[test]
+    fn it_works() {
+        assert_eq!(
+            process(EXAMPLE, DEFAULT_OPERATORS),
+            Ok("190 = 10 * 19
+3267 = 81 * 40 + 27
+83: 17 5 has no solution
+156 = 15 || 6
+7290 = 6 * 8 || 6 * 15
+161011: 16 10 13 has no solution
+192 = 17 || 8 + 14
+21037: 9 7 18 13 has no solution
+292 = 11 + 6 * 16 + 20
+total: 11387
+"
+            .to_string())
+        );
+        assert_eq!(
+            process("83: 17 5\n3: 17 5", "-,/,^"),
+            Ok("83: 17 5 has no solution\n3 = 17 / 5\ntotal: 3\n".to_string())
+        );
+        assert_eq!(
+            process(EXAMPLE, "+,%"),
+            Err(UnknownOperator("%".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_solve() {
+        let registry = Registry::default();
+        let equation = Equation::parse("3267: 81 40 27").unwrap();
+        let solution = equation
+            .solve(&registry.select("+,*").unwrap())
+            .unwrap()
+            .unwrap();
+        assert_eq!(symbols(&solution), vec!["*", "+"]);
+        assert_eq!(equation.evaluate(&solution), Ok(Some(3267)));
+        // both work, the operators are tried in the order they're given
+        // starting with the last one
+        let solution = equation.solve(&registry.select("*,+").unwrap());
+        assert_eq!(symbols(&solution.unwrap().unwrap()), vec!["+", "*"]);
+
+        let operators = registry.select(DEFAULT_OPERATORS).unwrap();
+        assert_eq!(
+            Equation::parse("83: 17 5").unwrap().is_solvable(&operators),
+            Ok(false)
+        );
+        let solution = Equation::parse("7: 7").unwrap().solve(&operators);
+        assert!(solution.unwrap().unwrap().is_empty());
+        // anything times zero is zero, whatever comes before it
+        let solution = Equation::parse("0: 3 4 0").unwrap().solve(&operators);
+        assert_eq!(symbols(&solution.unwrap().unwrap()), vec!["+", "*"]);
+
+        let solution = Equation::parse("5: 7 2")
+            .unwrap()
+            .solve(&registry.select("/,^,-").unwrap());
+        assert_eq!(symbols(&solution.unwrap().unwrap()), vec!["^"]);
+
+        // 1005 rounds down to 3, and is found by going forwards
+        let equation = Equation::parse("3: 2 3 1000 333").unwrap();
+        let solution = equation
+            .solve(&registry.select("+,*,/").unwrap())
+            .unwrap()
+            .unwrap();
+        assert_eq!(symbols(&solution), vec!["+", "+", "/"]);
+    }
+
+    #[test]
+    fn test_overflow() {
+        let registry = Registry::default();
+        let operators = registry.select("*,-").unwrap();
+
+        // 2^32 * 2^32 - 1 is u64::MAX, but a u64 can't hold the 2^64 in between
+        let equation = Equation::parse("18446744073709551615: 4294967296 4294967296 1").unwrap();
+        assert!(matches!(equation.solve(&operators), Err(Overflow)));
+        assert_eq!(equation.evaluate(&operators), Err(Overflow));
+        // the other order works without overflowing
+        let equation = Equation::parse("0: 4294967296 4294967296 1").unwrap();
+        let solution = equation.solve(&operators);
+        assert_eq!(symbols(&solution.unwrap().unwrap()), vec!["-", "*"]);
+
+        let equation = Equation::parse("0: 4294967296 4294967296 0").unwrap();
+        let solution = equation.solve(&registry.select("*").unwrap());
+        assert!(matches!(solution, Err(Overflow)));
+    }
+
+    #[test]
+    fn test_matches_every_combination() {
+        let operators = Registry::default().select("+,-,*,/,^,**,||,||2").unwrap();
+        let mut seed = 7_u64;
+        let mut random = |below: u64| {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            seed % below
+        };
+
+        for _ in 0..300 {
+            let operands: Vec<u64> = (0..2 + random(3)).map(|_| random(12)).collect();
+            let sequences: Vec<Vec<Arc<dyn Operator>>> = (0..operands.len() - 1)
+                .map(|_| operators.iter().cloned())
+                .multi_cartesian_product()
+                .collect();
+            let mut equation = Equation { total: 0, operands };
+
+            let mut totals: Vec<u64> = (0..4)
+                .filter_map(|_| {
+                    let sequence = &sequences[random(sequences.len() as u64) as usize];
+                    equation.evaluate(sequence).ok().flatten()
+                })
+                .collect();
+            totals.push(random(100));
+
+            for total in totals {
+                equation.total = total;
+                let expected = sequences
+                    .iter()
+                    .any(|sequence| equation.evaluate(sequence) == Ok(Some(total)));
+                match equation.solve(&operators) {
+                    Ok(Some(solution)) => {
+                        assert!(expected, "{equation:?}");
+                        assert_eq!(equation.evaluate(&solution), Ok(Some(total)));
+                    }
+                    Ok(None) | Err(Overflow) => assert!(!expected, "{equation:?}"),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "bench")]
+pub mod benchmarks {
+    use super::{DEFAULT_OPERATORS, INPUT};
+
+    #[divan::bench()]
+    fn bench_process() {
+        super::process(INPUT, DEFAULT_OPERATORS).unwrap();
+    }
+}
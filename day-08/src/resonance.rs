@@ -0,0 +1,239 @@
+use glam::IVec2;
+use itertools::Itertools;
+use std::collections::{HashMap, HashSet};
+use std::ops::RangeInclusive;
+
+#[derive(Debug, Clone)]
+pub struct Antennas {
+    // number of columns and rows
+    pub size: IVec2,
+    pub towers: HashMap<char, Vec<IVec2>>,
+}
+
+impl Antennas {
+    pub fn parse(input: &str) -> Self {
+        let mut towers: HashMap<char, Vec<IVec2>> = HashMap::new();
+        let mut size = IVec2::ZERO;
+        for (y, line) in input.lines().enumerate() {
+            size.y = y as i32 + 1;
+            size.x = size.x.max(line.len() as i32);
+            for (x, c) in line.chars().enumerate() {
+                if c != '.' {
+                    towers
+                        .entry(c)
+                        .or_default()
+                        .push(IVec2::new(x as i32, y as i32));
+                }
+            }
+        }
+        Self { size, towers }
+    }
+
+    #[inline]
+    pub fn contains(&self, position: IVec2) -> bool {
+        (0..self.size.x).contains(&position.x) && (0..self.size.y).contains(&position.y)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Harmonics {
+    // one step beyond each tower
+    First,
+    // every position on the line through both towers
+    All,
+    // `n` steps beyond each tower for every `n` in the range, 0 being the
+    // towers themselves
+    Range(RangeInclusive<u32>),
+}
+
+// where antinodes form for a pair of towers of the same frequency. A step is
+// the offset from one tower to the other, or that offset divided by the gcd
+// of its coordinates when steps are reduced, so that a line like (0, 0) to
+// (2, 4) also goes through (1, 2).
+#[derive(Debug, Clone)]
+pub struct Resonance {
+    harmonics: Harmonics,
+    towers: bool,
+    reduced: bool,
+}
+
+impl Resonance {
+    pub fn new(harmonics: Harmonics) -> Self {
+        Self {
+            harmonics,
+            towers: false,
+            reduced: false,
+        }
+    }
+
+    // every tower that shares its frequency with another one is an antinode
+    pub fn with_towers(mut self) -> Self {
+        self.towers = true;
+        self
+    }
+
+    pub fn with_reduced_steps(mut self) -> Self {
+        self.reduced = true;
+        self
+    }
+
+    // the antinodes on the map for each frequency
+    pub fn antinodes(&self, antennas: &Antennas) -> HashMap<char, HashSet<IVec2>> {
+        antennas
+            .towers
+            .iter()
+            .map(|(&frequency, towers)| {
+                let mut antinodes = HashSet::new();
+                for (&a, &b) in towers.iter().tuple_combinations() {
+                    self.pair(antennas, a, b, &mut antinodes);
+                }
+                (frequency, antinodes)
+            })
+            .collect()
+    }
+
+    // the number of positions with an antinode of any frequency
+    pub fn count(&self, antennas: &Antennas) -> usize {
+        self.antinodes(antennas)
+            .into_values()
+            .flatten()
+            .collect::<HashSet<_>>()
+            .len()
+    }
+
+    fn pair(&self, antennas: &Antennas, a: IVec2, b: IVec2, antinodes: &mut HashSet<IVec2>) {
+        let offset = b - a;
+        let steps = if self.reduced {
+            gcd(offset.x, offset.y).max(1)
+        } else {
+            1
+        };
+        let step = offset / steps;
+
+        if self.towers {
+            antinodes.insert(a);
+            antinodes.insert(b);
+        }
+
+        // `n` steps before `a` and after `b`, a line that has left the map
+        // never comes back, so there's nothing left once both are off it
+        let mut beyond = |n: i32| {
+            let mut inside = false;
+            for antinode in [a - step * n, b + step * n] {
+                if antennas.contains(antinode) {
+                    antinodes.insert(antinode);
+                    inside = true;
+                }
+            }
+            inside
+        };
+
+        match &self.harmonics {
+            Harmonics::First => {
+                beyond(1);
+            }
+            Harmonics::All => {
+                let mut n = 1;
+                while beyond(n) {
+                    n += 1;
+                }
+                antinodes.extend((1..steps).map(|n| a + step * n));
+            }
+            Harmonics::Range(range) => {
+                // every step moves at least one row or column
+                let last = antennas.size.max_element().max(0) as u32;
+                for n in range.clone().take_while(|&n| n <= last) {
+                    if !beyond(n as i32) {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn gcd(a: i32, b: i32) -> i32 {
+    let (mut a, mut b) = (a.abs(), b.abs());
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "............
+........0...
+.....0......
+.......0....
+....0.......
+......A.....
+............
+............
+........A...
+.........A..
+............
+............";
+
+    #[test]
+    fn test_harmonics() {
+        let antennas = Antennas::parse(EXAMPLE);
+        assert_eq!(Resonance::new(Harmonics::First).count(&antennas), 14);
+        assert_eq!(Resonance::new(Harmonics::Range(1..=1)).count(&antennas), 14);
+        assert_eq!(
+            Resonance::new(Harmonics::All)
+                .with_towers()
+                .with_reduced_steps()
+                .count(&antennas),
+            34
+        );
+        // the towers are the 0th harmonic
+        assert_eq!(
+            Resonance::new(Harmonics::Range(0..=u32::MAX)).count(&antennas),
+            34
+        );
+
+        let antinodes = Resonance::new(Harmonics::First).antinodes(&antennas);
+        assert_eq!(antinodes[&'A'].len(), 5);
+        assert!(antinodes[&'A'].contains(&IVec2::new(7, 7)));
+        assert_eq!(antinodes[&'0'].len(), 10);
+    }
+
+    #[test]
+    fn test_reduced_steps() {
+        let antennas = Antennas::parse(
+            "a....
+.....
+..a..
+.....
+.....",
+        );
+        let line = |resonance: Resonance| {
+            let mut antinodes: Vec<_> = resonance.antinodes(&antennas)[&'a']
+                .iter()
+                .map(|p| (p.x, p.y))
+                .collect();
+            antinodes.sort();
+            antinodes
+        };
+
+        assert_eq!(
+            line(Resonance::new(Harmonics::All).with_towers()),
+            vec![(0, 0), (2, 2), (4, 4)]
+        );
+        assert_eq!(
+            line(
+                Resonance::new(Harmonics::All)
+                    .with_towers()
+                    .with_reduced_steps()
+            ),
+            vec![(0, 0), (1, 1), (2, 2), (3, 3), (4, 4)]
+        );
+        assert_eq!(
+            line(Resonance::new(Harmonics::Range(1..=2)).with_reduced_steps()),
+            vec![(3, 3), (4, 4)]
+        );
+    }
+}
@@ -0,0 +1,4 @@
+// prints the checksum, moves and fragmentation for each compaction strategy
+fn main() {
+    print!("{}", day_09::compaction::run());
+}
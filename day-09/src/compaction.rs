@@ -0,0 +1,349 @@
+use crate::disk::{Disk, Extent, FreeSpace};
+
+const INPUT: &str = include_str!("input.txt");
+
+// moves files towards the start of the disk, returning the number of
+// extents that were moved
+pub trait CompactionStrategy {
+    fn name(&self) -> &'static str;
+
+    fn compact(&self, disk: &mut Disk) -> usize;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Report {
+    pub checksum: usize,
+    pub moves: usize,
+    pub fragmentation: Fragmentation,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fragmentation {
+    // free spans before the end of the last file
+    pub gaps: usize,
+    // files that are in more than one piece
+    pub split_files: usize,
+}
+
+// fills free space from the left with blocks from the end of the disk,
+// splitting files up where they don't fit, like part 1
+#[derive(Debug)]
+pub struct Fragment;
+
+impl CompactionStrategy for Fragment {
+    fn name(&self) -> &'static str {
+        "fragment"
+    }
+
+    fn compact(&self, disk: &mut Disk) -> usize {
+        let mut files: Vec<Extent> = disk
+            .files
+            .iter()
+            .filter(|file| file.len > 0)
+            .copied()
+            .collect();
+        files.sort_unstable_by_key(|file| file.offset);
+        let mut free = disk.free.clone();
+        let mut span = 0;
+
+        let mut compacted = Vec::with_capacity(files.len());
+        let mut moves = 0;
+        while let Some(mut file) = files.pop() {
+            // the leftmost free block is past every file that's left, so
+            // nothing else can move
+            if free.get(span).is_none_or(|span| span.offset > file.offset) {
+                compacted.push(file);
+                compacted.append(&mut files);
+                break;
+            }
+
+            // the end of the file fills the start of the span
+            let len = free[span].len.min(file.len);
+            compacted.push(Extent {
+                id: file.id,
+                offset: free[span].offset,
+                len,
+            });
+            moves += 1;
+
+            free[span].offset += len;
+            free[span].len -= len;
+            if free[span].len == 0 {
+                span += 1;
+            }
+            file.len -= len;
+            if file.len > 0 {
+                files.push(file);
+            }
+        }
+
+        compacted.sort_unstable_by_key(|file| (file.id, file.offset));
+        disk.files = compacted;
+        moves
+    }
+}
+
+// moves every file, highest id first, to the leftmost free span it fits in
+// that is before it. Files only ever move left, past the files with a lower
+// id that are yet to move, so the space they leave behind is never used again.
+#[derive(Debug)]
+pub struct FirstFit;
+
+impl CompactionStrategy for FirstFit {
+    fn name(&self) -> &'static str {
+        "first-fit"
+    }
+
+    fn compact(&self, disk: &mut Disk) -> usize {
+        let mut index = FreeSpace::new(&disk.free);
+        let mut moves = 0;
+        for file in disk.files.iter_mut().rev().filter(|file| file.len > 0) {
+            if let Some(offset) = index.take_first_fit(file.len, file.offset) {
+                file.offset = offset;
+                moves += 1;
+            }
+        }
+        moves
+    }
+}
+
+// same as `FirstFit`, but picks the shortest free span that the file fits in
+#[derive(Debug)]
+pub struct BestFit;
+
+impl CompactionStrategy for BestFit {
+    fn name(&self) -> &'static str {
+        "best-fit"
+    }
+
+    fn compact(&self, disk: &mut Disk) -> usize {
+        let mut index = FreeSpace::new(&disk.free);
+        let mut moves = 0;
+        for file in disk.files.iter_mut().rev().filter(|file| file.len > 0) {
+            if let Some(offset) = index.take_best_fit(file.len, file.offset) {
+                file.offset = offset;
+                moves += 1;
+            }
+        }
+        moves
+    }
+}
+
+// slides every file left, in order, until there's no free space between them
+#[derive(Debug)]
+pub struct Defragment;
+
+impl CompactionStrategy for Defragment {
+    fn name(&self) -> &'static str {
+        "defragment"
+    }
+
+    fn compact(&self, disk: &mut Disk) -> usize {
+        let mut files: Vec<&mut Extent> =
+            disk.files.iter_mut().filter(|file| file.len > 0).collect();
+        files.sort_unstable_by_key(|file| file.offset);
+
+        let mut offset = 0;
+        let mut moves = 0;
+        for file in files {
+            if file.offset != offset {
+                file.offset = offset;
+                moves += 1;
+            }
+            offset += file.len;
+        }
+        moves
+    }
+}
+
+#[tracing::instrument(level = "trace", skip())]
+pub fn run() -> String {
+    process(INPUT)
+}
+
+#[tracing::instrument(level = "trace", skip(input))]
+fn process(input: &str) -> String {
+    let disk = Disk::parse(input);
+    let strategies: [&dyn CompactionStrategy; 4] = [&Fragment, &FirstFit, &BestFit, &Defragment];
+
+    let mut report = String::new();
+    for strategy in strategies {
+        let Report {
+            checksum,
+            moves,
+            fragmentation,
+        } = disk.clone().compact(strategy);
+        report.push_str(&format!(
+            "{}: checksum {checksum}, {moves} moves, {} gaps, {} split files\n",
+            strategy.name(),
+            fragmentation.gaps,
+            fragmentation.split_files
+        ));
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::disk::Span;
+
+    const EXAMPLE: &str = "2333133121414131402";
+
+    #[test]
+    fn it_works() {
+        assert_eq!(
+            process(EXAMPLE),
+            "fragment: checksum 1928, 7 moves, 0 gaps, 2 split files
+first-fit: checksum 2858, 4 moves, 5 gaps, 0 split files
+best-fit: checksum 2858, 4 moves, 5 gaps, 0 split files
+defragment: checksum 2453, 9 moves, 0 gaps, 0 split files
+"
+        );
+    }
+
+    #[test]
+    fn test_layout() {
+        let mut disk = Disk::parse(EXAMPLE);
+        assert_eq!(
+            disk.to_string(),
+            "00...111...2...333.44.5555.6666.777.888899"
+        );
+
+        let mut fragmented = disk.clone();
+        fragmented.compact(&Fragment);
+        assert_eq!(
+            fragmented.to_string(),
+            "0099811188827773336446555566.............."
+        );
+
+        let mut defragmented = disk.clone();
+        defragmented.compact(&Defragment);
+        assert_eq!(
+            defragmented.to_string(),
+            "0011123334455556666777888899.............."
+        );
+
+        disk.compact(&FirstFit);
+        assert_eq!(
+            disk.to_string(),
+            "00992111777.44.333....5555.6666.....8888.."
+        );
+    }
+
+    #[test]
+    fn test_first_fit() {
+        let mut disk = Disk::parse(EXAMPLE);
+        assert_eq!(disk.files.len(), 10);
+        assert_eq!(disk.free.len(), 8);
+
+        let report = disk.compact(&FirstFit);
+        assert_eq!(report.moves, 4);
+        assert_eq!(report.checksum, 2858);
+        assert_eq!(
+            disk.files[9],
+            Extent {
+                id: 9,
+                offset: 2,
+                len: 2
+            }
+        );
+        // the space that 2 left behind at 11 is free again
+        assert_eq!(disk.free[0], Span { offset: 11, len: 1 });
+        assert_eq!(disk.free[1], Span { offset: 14, len: 1 });
+    }
+
+    #[test]
+    fn test_best_fit() {
+        // 2 fits in both spans, but only just in the second one
+        let mut disk = Disk::parse("03122");
+        assert_eq!(disk.to_string(), "...1..22");
+        let report = disk.compact(&BestFit);
+        assert_eq!(disk.to_string(), "1...22..");
+        assert_eq!(report.moves, 2);
+        assert_eq!(
+            report.fragmentation,
+            Fragmentation {
+                gaps: 1,
+                split_files: 0
+            }
+        );
+
+        let mut disk = Disk::parse("03122");
+        disk.compact(&FirstFit);
+        assert_eq!(disk.to_string(), "221.....");
+    }
+
+    #[test]
+    fn test_matches_part1() {
+        let mut state: u64 = 2024;
+        // part 1 expects some free space after the first file
+        for len in [3, 5, 19, 201, 2001] {
+            let input: String = (0..len)
+                .map(|i| {
+                    // xorshift64
+                    state ^= state << 13;
+                    state ^= state >> 7;
+                    state ^= state << 17;
+                    let digit = match i % 2 {
+                        0 => 1 + state % 9,
+                        _ => state % 10,
+                    };
+                    char::from(b'0' + digit as u8)
+                })
+                .collect();
+
+            let mut disk = Disk::parse(&input);
+            let report = disk.compact(&Fragment);
+            assert_eq!(report.checksum, crate::part1::process(&input), "{input}");
+            assert_eq!(report.fragmentation.gaps, 0, "{input}");
+        }
+    }
+}
+
+#[cfg(feature = "bench")]
+pub mod benchmarks {
+    use super::*;
+    use std::sync::LazyLock;
+
+    // a disk map with two million entries
+    static DISK_MAP: LazyLock<String> = LazyLock::new(|| {
+        let mut state: u64 = 2024;
+        (0..2_000_000)
+            .map(|_| {
+                // xorshift64
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                char::from(b'0' + (state % 10) as u8)
+            })
+            .collect()
+    });
+
+    fn bench_strategy(bencher: divan::Bencher, strategy: &dyn CompactionStrategy) {
+        let disk = Disk::parse(&DISK_MAP);
+        bencher
+            .with_inputs(|| disk.clone())
+            .bench_local_values(|mut disk| disk.compact(strategy));
+    }
+
+    #[divan::bench(sample_count = 10)]
+    fn bench_fragment(bencher: divan::Bencher) {
+        bench_strategy(bencher, &Fragment);
+    }
+
+    #[divan::bench(sample_count = 10)]
+    fn bench_first_fit(bencher: divan::Bencher) {
+        bench_strategy(bencher, &FirstFit);
+    }
+
+    #[divan::bench(sample_count = 10)]
+    fn bench_best_fit(bencher: divan::Bencher) {
+        bench_strategy(bencher, &BestFit);
+    }
+
+    #[divan::bench(sample_count = 10)]
+    fn bench_defragment(bencher: divan::Bencher) {
+        bench_strategy(bencher, &Defragment);
+    }
+}
@@ -0,0 +1,309 @@
+use crate::compaction::{CompactionStrategy, Fragmentation, Report};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::fmt::Display;
+
+// files come from single digits of the disk map, so they're never longer
+// than this. Free spans can be, when an empty file is between them.
+const MAX_FILE: usize = 9;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Extent {
+    pub id: usize,
+    pub offset: usize,
+    pub len: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub offset: usize,
+    pub len: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct Disk {
+    // in order of id before compacting, a file that is split up has an
+    // extent for each part
+    pub files: Vec<Extent>,
+    // the free spans between files, in order on disk
+    pub free: Vec<Span>,
+    // in blocks, including any free space at the end
+    pub size: usize,
+}
+
+impl Disk {
+    pub fn parse(input: &str) -> Self {
+        let mut files = Vec::new();
+        let mut free: Vec<Span> = Vec::new();
+        let mut offset = 0;
+        for (i, len) in input.trim().bytes().enumerate() {
+            let len = (len - b'0') as usize;
+            if i % 2 == 0 {
+                files.push(Extent {
+                    id: i / 2,
+                    offset,
+                    len,
+                });
+            } else if len > 0 {
+                match free.last_mut() {
+                    Some(last) if last.offset + last.len == offset => last.len += len,
+                    _ => free.push(Span { offset, len }),
+                }
+            }
+            offset += len;
+        }
+
+        Self {
+            files,
+            free,
+            size: offset,
+        }
+    }
+
+    pub fn compact(&mut self, strategy: &dyn CompactionStrategy) -> Report {
+        let moves = strategy.compact(self);
+        self.free = self.gaps();
+
+        Report {
+            checksum: self.checksum(),
+            moves,
+            fragmentation: self.fragmentation(),
+        }
+    }
+
+    pub fn checksum(&self) -> usize {
+        self.files
+            .iter()
+            .map(|file| {
+                file.id * (file.len * file.offset + file.len * file.len.saturating_sub(1) / 2)
+            })
+            .sum()
+    }
+
+    pub fn fragmentation(&self) -> Fragmentation {
+        let mut extents: HashMap<usize, usize> = HashMap::new();
+        for file in self.files.iter().filter(|file| file.len > 0) {
+            *extents.entry(file.id).or_default() += 1;
+        }
+        let end = self
+            .files
+            .iter()
+            .map(|file| file.offset + file.len)
+            .max()
+            .unwrap_or(0);
+
+        Fragmentation {
+            gaps: self.free.iter().filter(|span| span.offset < end).count(),
+            split_files: extents.values().filter(|&&count| count > 1).count(),
+        }
+    }
+
+    // the free spans left between the files wherever they are now
+    fn gaps(&self) -> Vec<Span> {
+        let mut files: Vec<&Extent> = self.files.iter().filter(|file| file.len > 0).collect();
+        files.sort_unstable_by_key(|file| file.offset);
+
+        let mut gaps = Vec::new();
+        let mut offset = 0;
+        for file in files.into_iter().chain([&Extent {
+            id: 0,
+            offset: self.size,
+            len: 0,
+        }]) {
+            if file.offset > offset {
+                gaps.push(Span {
+                    offset,
+                    len: file.offset - offset,
+                });
+            }
+            offset = file.offset + file.len;
+        }
+        gaps
+    }
+
+    pub fn blocks(&self) -> Vec<Block> {
+        let mut blocks: Vec<Block> = (0..self.size)
+            .map(|index| Block {
+                index,
+                len: 1,
+                id: None,
+            })
+            .collect();
+        for file in &self.files {
+            for block in &mut blocks[file.offset..file.offset + file.len] {
+                *block = Block {
+                    index: file.offset,
+                    len: file.len,
+                    id: Some(file.id),
+                };
+            }
+        }
+        blocks
+    }
+}
+
+// the layout in the puzzle's format, `00...111...2...`
+impl Display for Disk {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for block in self.blocks() {
+            write!(f, "{block}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct Block {
+    pub index: usize,
+    pub len: usize,
+    pub id: Option<usize>,
+}
+
+impl Block {
+    pub fn is_space(&self) -> bool {
+        self.id.is_none()
+    }
+}
+
+impl Display for Block {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.id {
+            Some(id) => write!(f, "{}", id),
+            None => write!(f, "."),
+        }
+    }
+}
+
+// the free spans bucketed by length, ordered by offset, so the leftmost span
+// that fits a file is the first of the heap tops for its length and longer.
+// Spans that fit any file share the last bucket.
+#[derive(Debug)]
+pub struct FreeSpace {
+    heaps: [BinaryHeap<Reverse<(usize, usize)>>; MAX_FILE + 1],
+}
+
+impl FreeSpace {
+    pub fn new(spans: &[Span]) -> Self {
+        let mut index = Self {
+            heaps: Default::default(),
+        };
+        for span in spans {
+            index.insert(*span);
+        }
+        index
+    }
+
+    #[inline]
+    fn insert(&mut self, span: Span) {
+        if span.len > 0 {
+            self.heaps[span.len.min(MAX_FILE)].push(Reverse((span.offset, span.len)));
+        }
+    }
+
+    // takes `len` blocks from the leftmost span that fits them
+    // and starts before `before`, putting whatever is left of the span back
+    pub fn take_first_fit(&mut self, len: usize, before: usize) -> Option<usize> {
+        debug_assert!(len > 0, "an empty file doesn't need any space");
+        assert!(len <= MAX_FILE, "file should be a single digit");
+        let bucket = (len..=MAX_FILE)
+            .filter(|&bucket| {
+                self.heaps[bucket]
+                    .peek()
+                    .is_some_and(|&Reverse((offset, _))| offset < before)
+            })
+            .min_by_key(|&bucket| self.heaps[bucket].peek().map(|&Reverse(span)| span))?;
+
+        self.take(bucket, len)
+    }
+
+    // takes `len` blocks from the shortest span that fits them and starts
+    // before `before`, the leftmost one if there are several. Spans of
+    // `MAX_FILE` blocks or more all count as the longest.
+    pub fn take_best_fit(&mut self, len: usize, before: usize) -> Option<usize> {
+        assert!(len <= MAX_FILE, "file should be a single digit");
+        let bucket = (len..=MAX_FILE).find(|&bucket| {
+            self.heaps[bucket]
+                .peek()
+                .is_some_and(|&Reverse((offset, _))| offset < before)
+        })?;
+        self.take(bucket, len)
+    }
+
+    fn take(&mut self, bucket: usize, len: usize) -> Option<usize> {
+        let Reverse((offset, span_len)) = self.heaps[bucket].pop()?;
+        self.insert(Span {
+            offset: offset + len,
+            len: span_len - len,
+        });
+        Some(offset)
+    }
+
+    // the spans that are still free, in order on disk
+    pub fn into_spans(self) -> Vec<Span> {
+        let mut spans: Vec<Span> = self
+            .heaps
+            .into_iter()
+            .flatten()
+            .map(|Reverse((offset, len))| Span { offset, len })
+            .collect();
+        spans.sort_unstable_by_key(|span| span.offset);
+        spans
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_take_first_fit() {
+        let mut index = FreeSpace::new(&[
+            Span { offset: 3, len: 2 },
+            Span { offset: 8, len: 5 },
+            Span { offset: 20, len: 9 },
+        ]);
+        assert_eq!(index.take_first_fit(3, 30), Some(8));
+        assert_eq!(index.take_first_fit(2, 30), Some(3));
+        assert_eq!(index.take_first_fit(2, 30), Some(11));
+        assert_eq!(index.take_first_fit(2, 10), None);
+        assert_eq!(index.into_spans(), vec![Span { offset: 20, len: 9 }]);
+
+        // an empty file doesn't split the space around it
+        let disk = Disk::parse("1405");
+        assert_eq!(disk.free, vec![Span { offset: 1, len: 9 }]);
+        let disk = Disk::parse("14061");
+        assert_eq!(disk.free, vec![Span { offset: 1, len: 10 }]);
+        let mut index = FreeSpace::new(&disk.free);
+        assert_eq!(index.take_first_fit(9, 20), Some(1));
+        assert_eq!(index.into_spans(), vec![Span { offset: 10, len: 1 }]);
+    }
+
+    #[test]
+    fn test_take_best_fit() {
+        let mut index = FreeSpace::new(&[
+            Span { offset: 3, len: 5 },
+            Span { offset: 8, len: 2 },
+            Span { offset: 20, len: 2 },
+        ]);
+        assert_eq!(index.take_best_fit(2, 30), Some(8));
+        assert_eq!(index.take_best_fit(1, 30), Some(20));
+        assert_eq!(index.take_best_fit(1, 30), Some(21));
+        assert_eq!(index.take_best_fit(1, 30), Some(3));
+        assert_eq!(index.take_best_fit(4, 30), Some(4));
+        assert_eq!(index.take_best_fit(1, 30), None);
+    }
+
+    #[test]
+    fn test_layout() {
+        let disk = Disk::parse("12345");
+        assert_eq!(disk.to_string(), "0..111....22222");
+        assert_eq!(disk.size, 15);
+        assert_eq!(
+            disk.fragmentation(),
+            Fragmentation {
+                gaps: 2,
+                split_files: 0
+            }
+        );
+    }
+}
@@ -1,2 +1,5 @@
+pub mod compaction;
+pub mod disk;
 pub mod part1;
 pub mod part2;
+pub mod part2_blocks;
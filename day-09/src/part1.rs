@@ -6,7 +6,7 @@ pub fn run() -> String {
 }
 
 #[tracing::instrument(level = "trace", skip(input))]
-fn process(input: &str) -> usize {
+pub(crate) fn process(input: &str) -> usize {
     let mut disk = parse(input);
 
     let mut head: usize = 0;
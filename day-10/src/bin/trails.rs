@@ -0,0 +1,8 @@
+// ranks the trailheads by rating, or prints the trails as a Graphviz graph
+// with `cargo run -p day-10 --bin trails -- --dot | dot -Tsvg > trails.svg`
+fn main() {
+    match std::env::args().nth(1).as_deref() {
+        Some("--dot") => print!("{}", day_10::trails::run_dot()),
+        _ => print!("{}", day_10::trails::run()),
+    }
+}
@@ -0,0 +1,443 @@
+use std::fmt::Write;
+
+use grid::{Direction, Grid, Vec2};
+
+const INPUT: &str = include_str!("input.txt");
+
+const DIRECTIONS: [grid::Direction; 4] = [Direction::N, Direction::E, Direction::S, Direction::W];
+
+const PEAK: u32 = 9;
+
+trait Vec2Ext {
+    fn at(x: usize, y: usize) -> Vec2;
+}
+
+impl Vec2Ext for Vec2 {
+    fn at(x: usize, y: usize) -> Vec2 {
+        Vec2::new(x as i32, y as i32)
+    }
+}
+
+trait GridExt {
+    fn get_digit(&self, point: &Vec2) -> Option<u32>;
+
+    fn get_neighbours(&self, point: &Vec2) -> Neighbours<'_>;
+}
+
+impl GridExt for Grid<'_> {
+    #[inline]
+    fn get_digit(&self, point: &Vec2) -> Option<u32> {
+        self.get(point).and_then(|c| c.to_digit(10))
+    }
+
+    #[inline]
+    fn get_neighbours(&self, point: &Vec2) -> Neighbours<'_> {
+        Neighbours {
+            grid: self,
+            point: point.clone(),
+            direction_index: 0,
+        }
+    }
+}
+
+struct Neighbours<'a> {
+    grid: &'a Grid<'a>,
+    point: Vec2,
+    direction_index: usize,
+}
+
+impl Iterator for Neighbours<'_> {
+    type Item = (Vec2, char);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.direction_index < 4 {
+            let point = &self.point + DIRECTIONS[self.direction_index];
+            self.direction_index += 1;
+            if let Some(c) = self.grid.get(&point) {
+                return Some((point, c));
+            }
+        }
+        None
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trailhead {
+    pub position: Vec2,
+    // the number of peaks that can be reached
+    pub score: usize,
+    // the number of distinct trails to any peak
+    pub rating: usize,
+    // in reading order
+    pub peaks: Vec<Vec2>,
+}
+
+// every step on the map that goes up by exactly one, which can't have any
+// cycles. Positions are numbered in reading order.
+#[derive(Debug, Clone)]
+pub struct TrailMap {
+    cols: usize,
+    heights: Vec<Option<u32>>,
+    uphill: Vec<Vec<usize>>,
+    // the positions at each height from 0 to 9, in reading order
+    levels: [Vec<usize>; PEAK as usize + 1],
+}
+
+impl TrailMap {
+    pub fn parse(input: &str) -> Self {
+        let grid = Grid::new(input);
+        let mut heights = Vec::with_capacity(grid.rows * grid.cols);
+        let mut uphill = Vec::with_capacity(grid.rows * grid.cols);
+        let mut levels: [Vec<usize>; PEAK as usize + 1] = Default::default();
+        for y in 0..grid.rows {
+            for x in 0..grid.cols {
+                let point = Vec2::at(x, y);
+                let height = grid.get_digit(&point).filter(|&height| height <= PEAK);
+                let mut steps = Vec::new();
+                if let Some(height) = height {
+                    levels[height as usize].push(heights.len());
+                    steps.extend(
+                        grid.get_neighbours(&point)
+                            .filter(|(_, c)| c.to_digit(10) == Some(height + 1))
+                            .map(|(neighbour, _)| {
+                                neighbour.y as usize * grid.cols + neighbour.x as usize
+                            }),
+                    );
+                }
+                heights.push(height);
+                uphill.push(steps);
+            }
+        }
+
+        Self {
+            cols: grid.cols,
+            heights,
+            uphill,
+            levels,
+        }
+    }
+
+    #[inline]
+    fn position(&self, index: usize) -> Vec2 {
+        Vec2::at(index % self.cols, index / self.cols)
+    }
+
+    // the number of distinct trails from each position to a peak, worked out
+    // from the peaks down so every step is only followed once
+    fn ratings(&self) -> Vec<usize> {
+        let mut ratings = vec![0; self.heights.len()];
+        for &peak in &self.levels[PEAK as usize] {
+            ratings[peak] = 1;
+        }
+        for level in self.levels[..PEAK as usize].iter().rev() {
+            for &index in level {
+                ratings[index] = self.uphill[index].iter().map(|&next| ratings[next]).sum();
+            }
+        }
+        ratings
+    }
+
+    // the peaks that can be reached from each position, in reading order
+    fn peaks(&self) -> Vec<Vec<usize>> {
+        let mut peaks = vec![Vec::new(); self.heights.len()];
+        for &peak in &self.levels[PEAK as usize] {
+            peaks[peak].push(peak);
+        }
+        for level in self.levels[..PEAK as usize].iter().rev() {
+            for &index in level {
+                let mut reachable: Vec<usize> = self.uphill[index]
+                    .iter()
+                    .flat_map(|&next| peaks[next].iter().copied())
+                    .collect();
+                reachable.sort_unstable();
+                reachable.dedup();
+                peaks[index] = reachable;
+            }
+        }
+        peaks
+    }
+
+    // every position at height 0, in reading order
+    pub fn trailheads(&self) -> Vec<Trailhead> {
+        let ratings = self.ratings();
+        let peaks = self.peaks();
+        self.levels[0]
+            .iter()
+            .map(|&index| Trailhead {
+                position: self.position(index),
+                score: peaks[index].len(),
+                rating: ratings[index],
+                peaks: peaks[index]
+                    .iter()
+                    .map(|&peak| self.position(peak))
+                    .collect(),
+            })
+            .collect()
+    }
+
+    // the sum of the scores of every trailhead
+    pub fn score(&self) -> usize {
+        let peaks = self.peaks();
+        self.levels[0].iter().map(|&index| peaks[index].len()).sum()
+    }
+
+    // the sum of the ratings of every trailhead
+    pub fn rating(&self) -> usize {
+        let ratings = self.ratings();
+        self.levels[0].iter().map(|&index| ratings[index]).sum()
+    }
+
+    // the map as a Graphviz graph, with a node for every position labelled
+    // with its height and an edge for every step up. Trailheads and peaks are
+    // filled in, and each height is drawn on its own rank.
+    pub fn to_dot(&self) -> String {
+        let id = |index: usize| {
+            let position = self.position(index);
+            format!("\"{},{}\"", position.x, position.y)
+        };
+
+        let mut dot = String::from("digraph trails {\n    rankdir=LR;\n");
+        for (height, level) in self.levels.iter().enumerate() {
+            if level.is_empty() {
+                continue;
+            }
+            let filled = match height as u32 {
+                0 | PEAK => ", style=filled",
+                _ => "",
+            };
+            let _ = write!(dot, "    subgraph {{\n        rank=same;\n");
+            for &index in level {
+                let _ = writeln!(dot, "        {} [label=\"{height}\"{filled}];", id(index));
+            }
+            let _ = writeln!(dot, "    }}");
+        }
+        for (index, steps) in self.uphill.iter().enumerate() {
+            for &next in steps {
+                let _ = writeln!(dot, "    {} -> {};", id(index), id(next));
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+// the trailheads with the most trails first, then the highest score
+#[tracing::instrument(level = "trace", skip())]
+pub fn run() -> String {
+    process(INPUT)
+}
+
+#[tracing::instrument(level = "trace", skip())]
+pub fn run_dot() -> String {
+    TrailMap::parse(INPUT).to_dot()
+}
+
+#[tracing::instrument(level = "trace", skip(input))]
+fn process(input: &str) -> String {
+    let mut trailheads = TrailMap::parse(input).trailheads();
+    trailheads.sort_by(|a, b| b.rating.cmp(&a.rating).then(b.score.cmp(&a.score)));
+
+    let mut report = String::new();
+    for Trailhead {
+        position,
+        score,
+        rating,
+        peaks,
+    } in trailheads
+    {
+        let peaks: Vec<String> = peaks
+            .iter()
+            .map(|peak| format!("({}, {})", peak.x, peak.y))
+            .collect();
+        let _ = writeln!(
+            report,
+            "({}, {}): rating {rating}, score {score}, peaks {}",
+            position.x,
+            position.y,
+            peaks.join(" ")
+        );
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "89010123
+78121874
+87430965
+96549874
+45678903
+32019012
+01329801
+10456732";
+
+    #[test]
+    fn test_neighbours() {
+        let grid = Grid::new(
+            "abc
+def
+ghi",
+        );
+
+        let mut neighbours = grid.get_neighbours(&Vec2::new(1, 1));
+
+        assert_eq!(neighbours.next(), Some((Vec2::new(1, 0), 'b')));
+        assert_eq!(neighbours.next(), Some((Vec2::new(2, 1), 'f')));
+        assert_eq!(neighbours.next(), Some((Vec2::new(1, 2), 'h')));
+        assert_eq!(neighbours.next(), Some((Vec2::new(0, 1), 'd')));
+        assert_eq!(neighbours.next(), None);
+
+        let mut neighbours = grid.get_neighbours(&Vec2::new(0, 0));
+
+        assert_eq!(neighbours.next(), Some((Vec2::new(1, 0), 'b')));
+        assert_eq!(neighbours.next(), Some((Vec2::new(0, 1), 'd')));
+        assert_eq!(neighbours.next(), None);
+    }
+
+    #[test]
+    fn test_trailheads() {
+        let map = TrailMap::parse(EXAMPLE);
+        let trailheads = map.trailheads();
+        assert_eq!(
+            trailheads.iter().map(|head| head.score).collect::<Vec<_>>(),
+            vec![5, 6, 5, 3, 1, 3, 5, 3, 5]
+        );
+        assert_eq!(
+            trailheads
+                .iter()
+                .map(|head| head.rating)
+                .collect::<Vec<_>>(),
+            vec![20, 24, 10, 4, 1, 4, 5, 8, 5]
+        );
+        assert_eq!(map.score(), 36);
+        assert_eq!(map.rating(), 81);
+
+        let head = &trailheads[4];
+        assert_eq!(head.position, Vec2::new(2, 5));
+        assert_eq!(head.peaks, vec![Vec2::new(4, 6)]);
+    }
+
+    #[test]
+    fn test_unreachable() {
+        // impassable tiles aren't part of any trail, and a trail has to climb
+        // by one at every step
+        let map = TrailMap::parse(
+            "...0...
+...1...
+...2...
+6543456
+7.....7
+8.....8
+9.....9",
+        );
+        let trailheads = map.trailheads();
+        assert_eq!(trailheads.len(), 1);
+        assert_eq!(trailheads[0].score, 2);
+        assert_eq!(trailheads[0].rating, 2);
+
+        let map = TrailMap::parse("0123\n9854");
+        assert_eq!(map.rating(), 0);
+        assert!(map.trailheads()[0].peaks.is_empty());
+    }
+
+    #[test]
+    fn test_matches_search() {
+        // follows every trail one step at a time
+        fn search(rows: &[Vec<u32>], x: usize, y: usize, peaks: &mut Vec<Vec2>) {
+            if rows[y][x] == PEAK {
+                peaks.push(Vec2::at(x, y));
+                return;
+            }
+            let next = [
+                (x, y.wrapping_sub(1)),
+                (x + 1, y),
+                (x, y + 1),
+                (x.wrapping_sub(1), y),
+            ];
+            for (nx, ny) in next {
+                if rows.get(ny).and_then(|row| row.get(nx)) == Some(&(rows[y][x] + 1)) {
+                    search(rows, nx, ny, peaks);
+                }
+            }
+        }
+
+        let mut state: u64 = 2024;
+        for _ in 0..50 {
+            let rows: Vec<Vec<u32>> = (0..12)
+                .map(|_| {
+                    (0..12)
+                        .map(|_| {
+                            // xorshift64
+                            state ^= state << 13;
+                            state ^= state >> 7;
+                            state ^= state << 17;
+                            (state % 10) as u32
+                        })
+                        .collect()
+                })
+                .collect();
+            let input = rows
+                .iter()
+                .map(|row| row.iter().map(u32::to_string).collect::<String>())
+                .collect::<Vec<_>>()
+                .join("\n");
+
+            for head in TrailMap::parse(&input).trailheads() {
+                let mut peaks = Vec::new();
+                search(
+                    &rows,
+                    head.position.x as usize,
+                    head.position.y as usize,
+                    &mut peaks,
+                );
+                assert_eq!(head.rating, peaks.len(), "{input}");
+                peaks.sort_by_key(|peak| (peak.y, peak.x));
+                peaks.dedup();
+                assert_eq!(head.peaks, peaks, "{input}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_dot() {
+        let dot = TrailMap::parse("01\n32").to_dot();
+        assert_eq!(
+            dot,
+            "digraph trails {
+    rankdir=LR;
+    subgraph {
+        rank=same;
+        \"0,0\" [label=\"0\", style=filled];
+    }
+    subgraph {
+        rank=same;
+        \"1,0\" [label=\"1\"];
+    }
+    subgraph {
+        rank=same;
+        \"1,1\" [label=\"2\"];
+    }
+    subgraph {
+        rank=same;
+        \"0,1\" [label=\"3\"];
+    }
+    \"0,0\" -> \"1,0\";
+    \"1,0\" -> \"1,1\";
+    \"1,1\" -> \"0,1\";
+}
+"
+        );
+    }
+}
+
+#[cfg(feature = "bench")]
+pub mod benchmarks {
+    use super::{TrailMap, INPUT};
+
+    #[divan::bench()]
+    fn bench_trailheads() {
+        TrailMap::parse(INPUT).trailheads();
+    }
+}
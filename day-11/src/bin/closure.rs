@@ -0,0 +1,10 @@
+// counts the stones after any number of blinks, exactly while they fit in a
+// u128 and modulo a prime after that, like
+// `cargo run -p day-11 --bin closure -- 1000000000000`
+fn main() {
+    let blinks = std::env::args()
+        .nth(1)
+        .map(|blinks| blinks.parse().expect("blinks should be a number"))
+        .unwrap_or(75);
+    print!("{}", day_11::transitions::run(blinks));
+}
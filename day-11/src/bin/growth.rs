@@ -0,0 +1,9 @@
+// prints the number of stones after each blink as csv, 75 blinks unless
+// another number is given like `cargo run -p day-11 --bin growth -- 100`
+fn main() {
+    let blinks = std::env::args()
+        .nth(1)
+        .map(|blinks| blinks.parse().expect("blinks should be a number"))
+        .unwrap_or(75);
+    print!("{}", day_11::rules::run(blinks));
+}
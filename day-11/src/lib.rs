@@ -1,3 +1,5 @@
 pub mod part1;
 pub mod part1_vecdeque;
 pub mod part2;
+pub mod rules;
+pub mod transitions;
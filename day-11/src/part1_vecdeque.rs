@@ -1,20 +1,24 @@
 use std::collections::VecDeque;
 
+use crate::rules::{parse, Overflow, Rules, Stone};
+
 const INPUT: &str = include_str!("input.txt");
 
 #[tracing::instrument(level = "trace", skip())]
 pub fn run() -> String {
-    process(INPUT, 25).to_string()
+    match process(INPUT, 25) {
+        Ok(count) => count.to_string(),
+        Err(error) => error.to_string(),
+    }
 }
 
-type BlinkItem = (u64, usize);
+type BlinkItem = (Stone, usize);
 
 #[tracing::instrument(level = "trace", skip(input))]
-fn process(input: &str, blinks: usize) -> usize {
-    let pebbles = input.trim().split_ascii_whitespace();
-
-    let mut queue = pebbles
-        .flat_map(|pebble| pebble.parse::<u64>())
+fn process(input: &str, blinks: usize) -> Result<usize, Overflow> {
+    let rules = Rules::default();
+    let mut queue = parse(input)
+        .into_iter()
         .map(|pebble| (pebble, blinks))
         .collect::<VecDeque<BlinkItem>>();
 
@@ -24,55 +28,29 @@ fn process(input: &str, blinks: usize) -> usize {
         if remaining == 0 {
             count += 1;
         } else {
-            match pebble {
-                // If the stone is engraved with the number 0, it is replaced by a stone engraved with the number 1.
-                0 => {
-                    queue.push_front((1, remaining - 1));
-                }
-                // If the stone is engraved with a number that has an even number of digits, it is replaced by two stones. The left half of the digits are engraved on the new left stone, and the right half of the digits are engraved on the new right stone. (The new numbers don't keep extra leading zeroes: 1000 would become stones 10 and 0.)
-                x if x.ilog10() % 2 == 1 => {
-                    let (a, b) = split_digits(x);
-                    queue.push_front((a, remaining - 1));
-                    queue.push_front((b, remaining - 1));
-                }
-                // If none of the other rules apply, the stone is replaced by a new stone; the old stone's number multiplied by 2024 is engraved on the new stone.
-                x => {
-                    queue.push_front((x * 2024, remaining - 1));
-                }
+            for pebble in rules.apply(pebble)? {
+                queue.push_front((pebble, remaining - 1));
             }
         }
     }
 
-    count
-}
-
-fn split_digits(value: u64) -> (u64, u64) {
-    let order = 10_u64.pow(value.ilog10() / 2 + 1);
-    (value / order, value % order)
+    Ok(count)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_split_digits() {
-        assert_eq!(split_digits(123456), (123, 456));
-        assert_eq!(split_digits(1234), (12, 34));
-        assert_eq!(split_digits(12), (1, 2));
-        assert_eq!(split_digits(1001), (10, 1));
-    }
-
     #[test]
     fn example_1() {
         let result = process("125 17", 6);
-        assert_eq!(result, 22);
+        assert_eq!(result, Ok(22));
     }
 
     #[test]
     fn example_2() {
         let result = process("125 17", 25);
-        assert_eq!(result, 55312);
+        assert_eq!(result, Ok(55312));
     }
 }
 
@@ -86,6 +64,6 @@ pub mod benchmarks {
 
     #[divan::bench()]
     fn bench_process_25() {
-        super::process(INPUT, 25);
+        super::process(INPUT, 25).unwrap();
     }
 }
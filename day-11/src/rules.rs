@@ -0,0 +1,389 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::iter::{Chain, Once};
+
+const INPUT: &str = include_str!("input.txt");
+
+// the value engraved on a stone
+pub type Stone = u128;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overflow {
+    // a rule would have engraved a value larger than a u128 on this stone
+    Stone(Stone),
+    // there are more stones than fit in a u128
+    Count,
+}
+
+impl fmt::Display for Overflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Overflow::Stone(stone) => write!(f, "stone {stone} grows past a u128"),
+            Overflow::Count => write!(f, "the number of stones doesn't fit in a u128"),
+        }
+    }
+}
+
+impl std::error::Error for Overflow {}
+
+// what a single stone turns into after a blink
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stones {
+    One(Stone),
+    Two(Stone, Stone),
+}
+
+impl IntoIterator for Stones {
+    type Item = Stone;
+    type IntoIter = Chain<Once<Stone>, std::option::IntoIter<Stone>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        match self {
+            Stones::One(stone) => std::iter::once(stone).chain(None),
+            Stones::Two(left, right) => std::iter::once(left).chain(Some(right)),
+        }
+    }
+}
+
+pub trait StoneRule: fmt::Debug + Send + Sync {
+    // `None` when the rule doesn't apply to the stone
+    fn apply(&self, stone: Stone) -> Result<Option<Stones>, Overflow>;
+}
+
+// the puzzle's first rule with `from` 0 and `to` 1:
+// If the stone is engraved with the number 0, it is replaced by a stone engraved with the number 1.
+#[derive(Debug)]
+pub struct Replace {
+    pub from: Stone,
+    pub to: Stone,
+}
+
+impl StoneRule for Replace {
+    fn apply(&self, stone: Stone) -> Result<Option<Stones>, Overflow> {
+        Ok((stone == self.from).then_some(Stones::One(self.to)))
+    }
+}
+
+// the puzzle's second rule in base 10:
+// If the stone is engraved with a number that has an even number of digits, it is replaced by two stones. The left half of the digits are engraved on the new left stone, and the right half of the digits are engraved on the new right stone. (The new numbers don't keep extra leading zeroes: 1000 would become stones 10 and 0.)
+#[derive(Debug)]
+pub struct SplitDigits {
+    base: u64,
+}
+
+impl SplitDigits {
+    pub fn new(base: u64) -> Self {
+        assert!(base >= 2, "base should be at least 2");
+        Self { base }
+    }
+}
+
+impl StoneRule for SplitDigits {
+    fn apply(&self, stone: Stone) -> Result<Option<Stones>, Overflow> {
+        Ok(split_digits(stone, self.base).map(|(left, right)| Stones::Two(left, right)))
+    }
+}
+
+// the left and right half of the digits of `value` in `base`, `None` when
+// there's an odd number of them. Dividing a u128 is a lot slower than a u64,
+// and most stones fit in one.
+#[inline]
+pub fn split_digits(value: Stone, base: u64) -> Option<(Stone, Stone)> {
+    if let Ok(value) = u64::try_from(value) {
+        let digits = value.checked_ilog(base).unwrap_or(0) + 1;
+        if digits % 2 == 1 {
+            return None;
+        }
+        let order = base.pow(digits / 2);
+        return Some(((value / order).into(), (value % order).into()));
+    }
+
+    let base = Stone::from(base);
+    let digits = value.ilog(base) + 1;
+    if digits % 2 == 1 {
+        return None;
+    }
+    let order = base.pow(digits / 2);
+    Some((value / order, value % order))
+}
+
+// the puzzle's last rule with 2024:
+// If none of the other rules apply, the stone is replaced by a new stone; the old stone's number multiplied by 2024 is engraved on the new stone.
+#[derive(Debug)]
+pub struct Multiply(pub Stone);
+
+impl StoneRule for Multiply {
+    fn apply(&self, stone: Stone) -> Result<Option<Stones>, Overflow> {
+        match stone.checked_mul(self.0) {
+            Some(stone) => Ok(Some(Stones::One(stone))),
+            None => Err(Overflow::Stone(stone)),
+        }
+    }
+}
+
+// rules that are tried in order until one applies, a stone that none of them
+// apply to stays the same
+#[derive(Debug)]
+pub struct Rules {
+    rules: Vec<Box<dyn StoneRule>>,
+}
+
+// the puzzle's rules
+impl Default for Rules {
+    fn default() -> Self {
+        Self::empty()
+            .with(Replace { from: 0, to: 1 })
+            .with(SplitDigits::new(10))
+            .with(Multiply(2024))
+    }
+}
+
+impl Rules {
+    pub fn empty() -> Self {
+        Self { rules: Vec::new() }
+    }
+
+    pub fn with(mut self, rule: impl StoneRule + 'static) -> Self {
+        self.rules.push(Box::new(rule));
+        self
+    }
+
+    #[inline]
+    pub fn apply(&self, stone: Stone) -> Result<Stones, Overflow> {
+        for rule in &self.rules {
+            if let Some(stones) = rule.apply(stone)? {
+                return Ok(stones);
+            }
+        }
+        Ok(Stones::One(stone))
+    }
+
+    // the number of stones with each value after one more blink, the order
+    // of the stones doesn't change how they evolve so only the counts are kept
+    pub fn blink(&self, stones: &HashMap<Stone, u128>) -> Result<HashMap<Stone, u128>, Overflow> {
+        let mut next: HashMap<Stone, u128> = HashMap::with_capacity(stones.len());
+        for (&stone, &count) in stones {
+            for stone in self.apply(stone)? {
+                let total = next.entry(stone).or_default();
+                *total = total.checked_add(count).ok_or(Overflow::Count)?;
+            }
+        }
+        Ok(next)
+    }
+
+    // the number of stones before the first blink and after each one, the
+    // blinks stop after the first one that overflows
+    pub fn blinks(&self, stones: &[Stone]) -> Blinks<'_> {
+        let mut counts = HashMap::new();
+        for &stone in stones {
+            *counts.entry(stone).or_default() += 1;
+        }
+        Blinks {
+            rules: self,
+            stones: counts,
+            started: false,
+            overflowed: false,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Blinks<'a> {
+    rules: &'a Rules,
+    stones: HashMap<Stone, u128>,
+    started: bool,
+    overflowed: bool,
+}
+
+impl Blinks<'_> {
+    // the number of stones with each value, as of the last blink that
+    // didn't overflow
+    pub fn stones(&self) -> &HashMap<Stone, u128> {
+        &self.stones
+    }
+}
+
+impl Iterator for Blinks<'_> {
+    type Item = Result<u128, Overflow>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.overflowed {
+            return None;
+        }
+        if self.started {
+            match self.rules.blink(&self.stones) {
+                Ok(stones) => self.stones = stones,
+                Err(error) => {
+                    self.overflowed = true;
+                    return Some(Err(error));
+                }
+            }
+        }
+        self.started = true;
+
+        let count = self
+            .stones
+            .values()
+            .try_fold(0_u128, |total, &count| total.checked_add(count))
+            .ok_or(Overflow::Count);
+        self.overflowed = count.is_err();
+        Some(count)
+    }
+}
+
+pub fn parse(input: &str) -> Vec<Stone> {
+    input
+        .split_ascii_whitespace()
+        .flat_map(|stone| stone.parse::<Stone>())
+        .collect()
+}
+
+// the number of stones after each blink as `blink,count` lines, for plotting
+#[tracing::instrument(level = "trace", skip())]
+pub fn run(blinks: usize) -> String {
+    process(INPUT, blinks)
+}
+
+#[tracing::instrument(level = "trace", skip(input))]
+fn process(input: &str, blinks: usize) -> String {
+    let rules = Rules::default();
+    let mut report = String::from("blink,count\n");
+    for (blink, count) in rules.blinks(&parse(input)).take(blinks + 1).enumerate() {
+        match count {
+            Ok(count) => report.push_str(&format!("{blink},{count}\n")),
+            Err(error) => report.push_str(&format!("{blink},{error}\n")),
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_digits() {
+        assert_eq!(split_digits(123456, 10), Some((123, 456)));
+        assert_eq!(split_digits(1234, 10), Some((12, 34)));
+        assert_eq!(split_digits(12, 10), Some((1, 2)));
+        assert_eq!(split_digits(1001, 10), Some((10, 1)));
+        assert_eq!(split_digits(123, 10), None);
+        assert_eq!(split_digits(0, 10), None);
+        assert_eq!(split_digits(0b1101, 2), Some((0b11, 0b01)));
+        assert_eq!(split_digits(0xabc, 16), None);
+    }
+
+    #[test]
+    fn test_split_digits_large() {
+        let value = 12345678901234567890123456789012345678;
+        assert_eq!(
+            split_digits(value, 10),
+            Some((1234567890123456789, 123456789012345678))
+        );
+        assert_eq!(split_digits(value / 10, 10), None);
+        assert_eq!(
+            split_digits(u128::MAX, 16),
+            Some((u64::MAX.into(), u64::MAX.into()))
+        );
+        // one more digit than a u64 has
+        let value = Stone::from(u64::MAX) + 1;
+        assert_eq!(split_digits(value, 2), None);
+    }
+
+    #[test]
+    fn test_blinks() {
+        let rules = Rules::default();
+        assert_eq!(
+            rules
+                .blinks(&[125, 17])
+                .take(7)
+                .collect::<Result<Vec<_>, _>>(),
+            Ok(vec![2, 3, 4, 5, 9, 13, 22])
+        );
+        assert_eq!(rules.blinks(&[125, 17]).nth(25), Some(Ok(55312)));
+
+        let mut blinks = rules.blinks(&[125, 17]);
+        blinks.nth(6);
+        assert_eq!(blinks.stones()[&2], 4);
+        assert_eq!(process("125 17", 3), "blink,count\n0,2\n1,3\n2,4\n3,5\n");
+    }
+
+    #[test]
+    fn test_overflow() {
+        let rules = Rules::default();
+        // 39 digits, the most a u128 has
+        let stone = 10_u128.pow(38);
+        let mut blinks = rules.blinks(&[stone]);
+        assert_eq!(blinks.next(), Some(Ok(1)));
+        assert_eq!(blinks.next(), Some(Err(Overflow::Stone(stone))));
+        assert_eq!(blinks.next(), None);
+        assert_eq!(blinks.stones()[&stone], 1);
+
+        // stones that fit in a u64 used to wrap around after a few blinks
+        let stone = 9_999_999_999_999_999_999;
+        let mut blinks = rules.blinks(&[stone]);
+        assert_eq!(blinks.nth(1), Some(Ok(1)));
+        assert_eq!(blinks.stones()[&(stone * 2024)], 1);
+
+        // every stone doubles, so after 127 blinks there are 2^128 of them
+        #[derive(Debug)]
+        struct Double;
+        impl StoneRule for Double {
+            fn apply(&self, stone: Stone) -> Result<Option<Stones>, Overflow> {
+                Ok(Some(Stones::Two(stone, stone)))
+            }
+        }
+        let rules = Rules::empty().with(Double);
+        assert_eq!(rules.blinks(&[1, 2]).nth(126), Some(Ok(1 << 127)));
+        assert_eq!(rules.blinks(&[1, 2]).nth(127), Some(Err(Overflow::Count)));
+        assert_eq!(rules.blinks(&[1]).nth(128), Some(Err(Overflow::Count)));
+        assert_eq!(
+            process("100000000000000000000000000000000000000", 5),
+            "blink,count\n0,1\n1,stone 100000000000000000000000000000000000000 grows past a u128\n"
+        );
+    }
+
+    #[test]
+    fn test_custom_rules() {
+        // split in binary and triple anything with an odd number of bits
+        let rules = Rules::empty()
+            .with(Replace { from: 0, to: 1 })
+            .with(SplitDigits::new(2))
+            .with(Multiply(3));
+        assert_eq!(rules.apply(0), Ok(Stones::One(1)));
+        assert_eq!(rules.apply(0b10), Ok(Stones::Two(1, 0)));
+        assert_eq!(rules.apply(0b101), Ok(Stones::One(15)));
+        // 1, 3, 1 1, 3 3, 1 1 1 1
+        assert_eq!(
+            rules.blinks(&[1]).take(5).collect::<Result<Vec<_>, _>>(),
+            Ok(vec![1, 1, 2, 2, 4])
+        );
+
+        // nothing applies, so nothing changes
+        let rules = Rules::empty().with(Replace { from: 0, to: 1 });
+        assert_eq!(rules.blinks(&[1, 2, 3]).nth(10), Some(Ok(3)));
+
+        #[derive(Debug)]
+        struct Halve;
+        impl StoneRule for Halve {
+            fn apply(&self, stone: Stone) -> Result<Option<Stones>, Overflow> {
+                Ok((stone % 2 == 0).then_some(Stones::Two(stone / 2, stone / 2)))
+            }
+        }
+        let rules = Rules::empty().with(Halve);
+        assert_eq!(
+            rules.blinks(&[8]).take(5).collect::<Result<Vec<_>, _>>(),
+            Ok(vec![1, 2, 4, 8, 8])
+        );
+    }
+}
+
+#[cfg(feature = "bench")]
+pub mod benchmarks {
+    use super::{parse, Rules, INPUT};
+
+    #[divan::bench(args = [25, 75])]
+    fn bench_blinks(blinks: usize) {
+        Rules::default().blinks(&parse(INPUT)).nth(blinks);
+    }
+}
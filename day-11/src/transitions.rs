@@ -0,0 +1,579 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+
+use crate::rules::{parse, Overflow, Rules, Stone};
+
+const INPUT: &str = include_str!("input.txt");
+
+// the largest prime below 2^32, so that the products of numbers below it
+// can be added up in a u128 without reducing them each time
+pub const PRIME: u32 = 4_294_967_291;
+
+// the most stone values a closure is allowed to find
+pub const LIMIT: usize = 100_000;
+
+// how many blinks an exact count goes through one at a time. Counts that
+// grow exponentially, like the puzzle's, overflow well before this.
+const STEPS: u64 = 1_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Modulus {
+    // counts stones exactly, failing with `Overflow::Count` once there are
+    // too many for a u128
+    Exact,
+    // counts stones modulo a prime, in time that only grows with the log of
+    // the number of blinks
+    Prime(u32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClosureError {
+    Overflow(Overflow),
+    // the rules keep making new values, so there's no finite set of them
+    TooMany(usize),
+}
+
+impl fmt::Display for ClosureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClosureError::Overflow(overflow) => write!(f, "{overflow}"),
+            ClosureError::TooMany(limit) => {
+                write!(f, "the stones take more than {limit} different values")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ClosureError {}
+
+impl From<Overflow> for ClosureError {
+    fn from(overflow: Overflow) -> Self {
+        ClosureError::Overflow(overflow)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CountError {
+    Overflow(Overflow),
+    // the recurrence is solved with inverses modulo the prime, which a
+    // composite modulus doesn't have
+    NotPrime(u32),
+}
+
+impl fmt::Display for CountError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CountError::Overflow(overflow) => write!(f, "{overflow}"),
+            CountError::NotPrime(modulus) => write!(f, "{modulus} isn't a prime"),
+        }
+    }
+}
+
+impl std::error::Error for CountError {}
+
+impl From<Overflow> for CountError {
+    fn from(overflow: Overflow) -> Self {
+        CountError::Overflow(overflow)
+    }
+}
+
+// every value the starting stones can ever take and what each of them turns
+// into after a blink, which is the transition matrix of the stone counts in
+// sparse form. Values are numbered in the order they're found.
+//
+// The matrix is too large to raise to a power directly, there are thousands
+// of values for the puzzle input and a cycle between over a thousand of
+// them. Instead the shortest recurrence that the total count follows is
+// found from its first terms, and the count after any number of blinks is
+// worked out from that recurrence's polynomial, like raising its companion
+// matrix to that power.
+#[derive(Debug, Clone)]
+pub struct Transitions {
+    pub stones: Vec<Stone>,
+    next: Vec<Vec<usize>>,
+    start: Vec<usize>,
+}
+
+impl Transitions {
+    pub fn close(rules: &Rules, stones: &[Stone], limit: usize) -> Result<Self, ClosureError> {
+        let mut index: HashMap<Stone, usize> = HashMap::new();
+        let mut values = Vec::new();
+        let mut queue = VecDeque::new();
+        let mut find = |stone: Stone, values: &mut Vec<Stone>, queue: &mut VecDeque<usize>| {
+            *index.entry(stone).or_insert_with(|| {
+                values.push(stone);
+                queue.push_back(values.len() - 1);
+                values.len() - 1
+            })
+        };
+
+        let start: Vec<usize> = stones
+            .iter()
+            .map(|&stone| find(stone, &mut values, &mut queue))
+            .collect();
+        let mut next = vec![Vec::new(); values.len()];
+        while let Some(i) = queue.pop_front() {
+            let targets: Vec<usize> = rules
+                .apply(values[i])?
+                .into_iter()
+                .map(|stone| find(stone, &mut values, &mut queue))
+                .collect();
+            if values.len() > limit {
+                return Err(ClosureError::TooMany(limit));
+            }
+            next.resize(values.len(), Vec::new());
+            next[i] = targets;
+        }
+
+        Ok(Self {
+            stones: values,
+            next,
+            start,
+        })
+    }
+
+    // the number of stones after `blinks` blinks
+    pub fn count(&self, blinks: u64, modulus: Modulus) -> Result<u128, CountError> {
+        match modulus {
+            Modulus::Exact => Ok(self.count_exact(blinks)?),
+            Modulus::Prime(prime) if !is_prime(prime) => Err(CountError::NotPrime(prime)),
+            Modulus::Prime(prime) => Ok(self.count_modulo(blinks, prime.into()).into()),
+        }
+    }
+
+    fn count_exact(&self, blinks: u64) -> Result<u128, Overflow> {
+        let mut counts = vec![0_u128; self.stones.len()];
+        for &i in &self.start {
+            counts[i] += 1;
+        }
+
+        let steps = blinks.min(STEPS);
+        for step in 0..steps {
+            let mut next = vec![0_u128; counts.len()];
+            for (i, &count) in counts.iter().enumerate() {
+                for &target in &self.next[i] {
+                    next[target] = next[target].checked_add(count).ok_or(Overflow::Count)?;
+                }
+            }
+            // nothing changes from here on
+            if next == counts {
+                break;
+            }
+            counts = next;
+            if step + 1 == steps && steps < blinks {
+                // still fitting after this long, the counts are going round
+                // a cycle or growing slowly, so the rest is done in one go
+                counts = self.jump(counts, blinks - steps);
+            }
+        }
+
+        counts
+            .into_iter()
+            .try_fold(0_u128, |total, count| total.checked_add(count))
+            .filter(|&total| total != u128::MAX)
+            .ok_or(Overflow::Count)
+    }
+
+    // the counts after `blinks` more blinks, from powers of the transition
+    // matrix found by squaring it. Sums and products saturate at u128::MAX,
+    // so every number worked out is the true one or u128::MAX if that's
+    // larger. Stones are never taken away, so any count that's used is at
+    // most the final total and a saturated one means that overflows too.
+    // Only a total of exactly u128::MAX is mistaken for an overflow.
+    fn jump(&self, mut counts: Vec<u128>, mut blinks: u64) -> Vec<u128> {
+        let n = self.stones.len();
+        let mut power = vec![vec![0_u128; n]; n];
+        for (i, targets) in self.next.iter().enumerate() {
+            for &target in targets {
+                power[i][target] += 1;
+            }
+        }
+
+        while blinks > 0 {
+            if blinks & 1 == 1 {
+                counts = (0..n)
+                    .map(|j| {
+                        (0..n).fold(0_u128, |total, i| {
+                            total.saturating_add(counts[i].saturating_mul(power[i][j]))
+                        })
+                    })
+                    .collect();
+            }
+            blinks >>= 1;
+            if blinks > 0 {
+                let mut square = vec![vec![0_u128; n]; n];
+                for (i, row) in power.iter().enumerate() {
+                    for (k, &a) in row.iter().enumerate().filter(|(_, &a)| a != 0) {
+                        for (j, &b) in power[k].iter().enumerate() {
+                            square[i][j] = square[i][j].saturating_add(a.saturating_mul(b));
+                        }
+                    }
+                }
+                power = square;
+            }
+        }
+        counts
+    }
+
+    fn count_modulo(&self, blinks: u64, prime: u64) -> u64 {
+        let mut counts = vec![0; self.stones.len()];
+        for &i in &self.start {
+            counts[i] = (counts[i] + 1) % prime;
+        }
+
+        // a recurrence can't be longer than the number of values, and twice
+        // its length in terms is enough to find it
+        let mut terms = Vec::with_capacity(2 * self.stones.len() + 1);
+        loop {
+            terms.push(
+                counts
+                    .iter()
+                    .fold(0, |total, count| (total + count) % prime),
+            );
+            if terms.len() as u64 > blinks {
+                return terms[blinks as usize];
+            }
+            if terms.len() > 2 * self.stones.len() {
+                break;
+            }
+
+            let mut next = vec![0; counts.len()];
+            for (i, &count) in counts.iter().enumerate() {
+                for &target in &self.next[i] {
+                    next[target] = (next[target] + count) % prime;
+                }
+            }
+            counts = next;
+        }
+
+        let recurrence = berlekamp_massey(&terms, prime);
+        let power = power_of_x(blinks, &recurrence, prime);
+        let total = power
+            .iter()
+            .zip(&terms)
+            .map(|(&a, &b)| a as u128 * b as u128)
+            .sum::<u128>();
+        (total % prime as u128) as u64
+    }
+}
+
+// the coefficients of the shortest recurrence
+// `terms[n] = c[0] * terms[n - 1] + c[1] * terms[n - 2] + ...` that the terms
+// follow, modulo a prime
+fn berlekamp_massey(terms: &[u64], prime: u64) -> Vec<u64> {
+    // `current` is `1 - c[0] x - c[1] x^2 ...`, and `previous` the last one
+    // before its length changed
+    let mut current = vec![1];
+    let mut previous = vec![1];
+    let mut len = 0;
+    let mut shift = 1;
+    let mut last_discrepancy = 1;
+
+    for n in 0..terms.len() {
+        let discrepancy = (0..=len)
+            .map(|i| current[i] as u128 * terms[n - i] as u128)
+            .sum::<u128>()
+            % prime as u128;
+        let discrepancy = discrepancy as u64;
+        if discrepancy == 0 {
+            shift += 1;
+            continue;
+        }
+
+        let scale = discrepancy * inverse(last_discrepancy, prime) % prime;
+        let before = current.clone();
+        current.resize(current.len().max(previous.len() + shift), 0);
+        for (i, &coefficient) in previous.iter().enumerate() {
+            let subtract = scale * coefficient % prime;
+            current[i + shift] = (current[i + shift] + prime - subtract) % prime;
+        }
+
+        if 2 * len <= n {
+            len = n + 1 - len;
+            previous = before;
+            last_discrepancy = discrepancy;
+            shift = 1;
+        } else {
+            shift += 1;
+        }
+    }
+
+    current.resize(len + 1, 0);
+    current[1..]
+        .iter()
+        .map(|&coefficient| (prime - coefficient) % prime)
+        .collect()
+}
+
+fn is_prime(n: u32) -> bool {
+    n >= 2
+        && (2..)
+            .take_while(|d| d * d <= n as u64)
+            .all(|d| n as u64 % d != 0)
+}
+
+#[inline]
+fn inverse(value: u64, prime: u64) -> u64 {
+    // Fermat's little theorem
+    let (mut base, mut exponent, mut result) = (value % prime, prime - 2, 1);
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = result * base % prime;
+        }
+        base = base * base % prime;
+        exponent >>= 1;
+    }
+    result
+}
+
+// x^n modulo the recurrence's polynomial `x^len - c[0] x^(len - 1) - ...`, as
+// the coefficients of 1, x, x^2 and so on. The nth term is these coefficients
+// applied to the first terms.
+fn power_of_x(n: u64, recurrence: &[u64], prime: u64) -> Vec<u64> {
+    let len = recurrence.len();
+    let mut result = vec![0; len];
+    if len == 0 {
+        return result;
+    }
+    result[0] = 1;
+
+    for bit in (0..u64::BITS - n.leading_zeros()).rev() {
+        result = square(&result, recurrence, prime);
+        if n >> bit & 1 == 1 {
+            // multiplying by x moves every coefficient up one, and x^len is
+            // replaced by the recurrence
+            let top = result[len - 1];
+            result.rotate_right(1);
+            result[0] = 0;
+            for (i, &coefficient) in recurrence.iter().enumerate() {
+                result[len - 1 - i] = (result[len - 1 - i] + top * coefficient) % prime;
+            }
+        }
+    }
+    result
+}
+
+// the products of numbers below 2^32 are added up in a u128 and only reduced
+// when they're needed, there can't be enough of them to overflow it
+fn square(poly: &[u64], recurrence: &[u64], prime: u64) -> Vec<u64> {
+    let len = poly.len();
+    let mut product = vec![0_u128; 2 * len - 1];
+    for (i, &a) in poly.iter().enumerate().filter(|(_, &a)| a != 0) {
+        for (j, &b) in poly.iter().enumerate() {
+            product[i + j] += a as u128 * b as u128;
+        }
+    }
+
+    // x^k for k >= len is c[0] x^(k - 1) + c[1] x^(k - 2) ...
+    for k in (len..2 * len - 1).rev() {
+        let top = (product[k] % prime as u128) as u64;
+        if top == 0 {
+            continue;
+        }
+        for (i, &coefficient) in recurrence.iter().enumerate() {
+            product[k - 1 - i] += top as u128 * coefficient as u128;
+        }
+    }
+    product[..len]
+        .iter()
+        .map(|&coefficient| (coefficient % prime as u128) as u64)
+        .collect()
+}
+
+// the number of stones after `blinks` blinks, exactly while it fits in a
+// u128 and modulo `PRIME`
+#[tracing::instrument(level = "trace", skip())]
+pub fn run(blinks: u64) -> String {
+    process(INPUT, blinks)
+}
+
+#[tracing::instrument(level = "trace", skip(input))]
+fn process(input: &str, blinks: u64) -> String {
+    let transitions = match Transitions::close(&Rules::default(), &parse(input), LIMIT) {
+        Ok(transitions) => transitions,
+        Err(error) => return format!("{error}\n"),
+    };
+
+    let mut report = format!("{} values\n", transitions.stones.len());
+    match transitions.count(blinks, Modulus::Exact) {
+        Ok(count) => report.push_str(&format!("{count} stones after {blinks} blinks\n")),
+        Err(error) => report.push_str(&format!("{error} after {blinks} blinks\n")),
+    }
+    let count = transitions
+        .count(blinks, Modulus::Prime(PRIME))
+        .expect("counting modulo a prime can't overflow");
+    report.push_str(&format!(
+        "{count} stones after {blinks} blinks, modulo {PRIME}\n"
+    ));
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // goes through every blink keeping the counts modulo a prime
+    fn stepped(rules: &Rules, stones: &[Stone], blinks: usize, prime: u64) -> u128 {
+        let mut counts: HashMap<Stone, u64> = HashMap::new();
+        for &stone in stones {
+            *counts.entry(stone).or_default() += 1;
+        }
+        for _ in 0..blinks {
+            let mut next: HashMap<Stone, u64> = HashMap::new();
+            for (&stone, &count) in &counts {
+                for stone in rules.apply(stone).unwrap() {
+                    let total = next.entry(stone).or_default();
+                    *total = (*total + count) % prime;
+                }
+            }
+            counts = next;
+        }
+        counts
+            .values()
+            .fold(0, |total, count| (total + count) % prime) as u128
+    }
+
+    #[test]
+    fn test_closure() {
+        let transitions = Transitions::close(&Rules::default(), &[0], LIMIT).unwrap();
+        assert_eq!(transitions.stones.len(), 54);
+        let transitions = Transitions::close(&Rules::default(), &[125, 17], LIMIT).unwrap();
+        assert_eq!(transitions.stones.len(), 76);
+        assert_eq!(transitions.count(0, Modulus::Exact), Ok(2));
+        assert_eq!(transitions.count(6, Modulus::Exact), Ok(22));
+        assert_eq!(transitions.count(25, Modulus::Exact), Ok(55312));
+
+        // tripling never comes back around
+        let rules = Rules::empty().with(crate::rules::Multiply(3));
+        assert_eq!(
+            Transitions::close(&rules, &[1], 5).unwrap_err(),
+            ClosureError::TooMany(5)
+        );
+        assert_eq!(
+            Transitions::close(&rules, &[1], LIMIT).unwrap_err(),
+            ClosureError::Overflow(Overflow::Stone(3_u128.pow(80)))
+        );
+    }
+
+    #[test]
+    fn test_matches_blinks() {
+        let rules = Rules::default();
+        for stones in [vec![125, 17], vec![0], vec![2024, 9999, 5678], vec![]] {
+            let transitions = Transitions::close(&rules, &stones, LIMIT).unwrap();
+            // past twice the number of values the counts come from the
+            // recurrence
+            for (blinks, count) in rules.blinks(&stones).take(200).enumerate() {
+                let count = count.unwrap();
+                let blinks = blinks as u64;
+                assert_eq!(transitions.count(blinks, Modulus::Exact), Ok(count));
+                assert_eq!(
+                    transitions.count(blinks, Modulus::Prime(PRIME)),
+                    Ok(count % PRIME as u128)
+                );
+                assert_eq!(
+                    transitions.count(blinks, Modulus::Prime(1_000_000_007)),
+                    Ok(count % 1_000_000_007)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_huge() {
+        let rules = Rules::default();
+        let transitions = Transitions::close(&rules, &[125, 17], LIMIT).unwrap();
+        for blinks in [500, 1000, 1234] {
+            assert_eq!(
+                transitions.count(blinks, Modulus::Prime(PRIME)),
+                Ok(stepped(&rules, &[125, 17], blinks as usize, PRIME.into()))
+            );
+        }
+        assert!(transitions
+            .count(1_000_000_000_000, Modulus::Prime(PRIME))
+            .is_ok());
+        assert_eq!(
+            transitions.count(1_000_000_000_000, Modulus::Exact),
+            Err(CountError::Overflow(Overflow::Count))
+        );
+
+        // nothing changes, so the count never grows
+        let rules = Rules::empty().with(crate::rules::Replace { from: 0, to: 1 });
+        let transitions = Transitions::close(&rules, &[1, 1, 5], LIMIT).unwrap();
+        assert_eq!(transitions.count(u64::MAX, Modulus::Exact), Ok(3));
+        assert_eq!(transitions.count(u64::MAX, Modulus::Prime(2)), Ok(1));
+        for modulus in [0, 1, 4, 1_000_000_000, 65_537 * 65_521] {
+            assert_eq!(
+                transitions.count(10, Modulus::Prime(modulus)),
+                Err(CountError::NotPrime(modulus))
+            );
+        }
+
+        // swapping back and forth never settles, but never grows either
+        let rules = Rules::empty()
+            .with(crate::rules::Replace { from: 1, to: 2 })
+            .with(crate::rules::Replace { from: 2, to: 1 });
+        let transitions = Transitions::close(&rules, &[1, 1, 5], LIMIT).unwrap();
+        assert_eq!(transitions.count(1_000_000_000_000, Modulus::Exact), Ok(3));
+        assert_eq!(transitions.count(u64::MAX, Modulus::Exact), Ok(3));
+    }
+
+    // stones below the depth leave a stone one higher behind every blink,
+    // so the count grows like a polynomial of that degree
+    #[derive(Debug)]
+    struct Trail(Stone);
+
+    impl crate::rules::StoneRule for Trail {
+        fn apply(&self, stone: Stone) -> Result<Option<crate::rules::Stones>, Overflow> {
+            Ok((stone < self.0).then_some(crate::rules::Stones::Two(stone, stone + 1)))
+        }
+    }
+
+    #[test]
+    fn test_slow_growth() {
+        let transitions =
+            Transitions::close(&Rules::empty().with(Trail(1)), &[0, 7], LIMIT).unwrap();
+        for blinks in [
+            0,
+            10,
+            STEPS - 1,
+            STEPS,
+            STEPS + 1,
+            1_000_000_000_000,
+            u64::MAX,
+        ] {
+            assert_eq!(
+                transitions.count(blinks, Modulus::Exact),
+                Ok(blinks as u128 + 2)
+            );
+        }
+
+        // the stones after n blinks are n choose 0 + n choose 1 + ... n
+        // choose 3
+        let transitions = Transitions::close(&Rules::empty().with(Trail(3)), &[0], LIMIT).unwrap();
+        for blinks in [10_u128, 5000, 1_000_000_000_000] {
+            let expected =
+                1 + blinks + blinks * (blinks - 1) / 2 + blinks * (blinks - 1) * (blinks - 2) / 6;
+            assert_eq!(
+                transitions.count(blinks as u64, Modulus::Exact),
+                Ok(expected)
+            );
+        }
+        assert_eq!(
+            transitions.count(u64::MAX, Modulus::Exact),
+            Err(CountError::Overflow(Overflow::Count))
+        );
+    }
+}
+
+#[cfg(feature = "bench")]
+pub mod benchmarks {
+    use super::{parse, Modulus, Rules, Transitions, INPUT, LIMIT, PRIME};
+
+    #[divan::bench(args = [75, 1_000_000_000_000])]
+    fn bench_count(blinks: u64) {
+        Transitions::close(&Rules::default(), &parse(INPUT), LIMIT)
+            .unwrap()
+            .count(blinks, Modulus::Prime(PRIME))
+            .unwrap();
+    }
+}
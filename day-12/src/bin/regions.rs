@@ -0,0 +1,8 @@
+// prints every region with its area, perimeter, sides and holes, or with
+// `--outlines` the corners of each of its fences, to plot them
+fn main() {
+    match std::env::args().nth(1).as_deref() {
+        Some("--outlines") => print!("{}", day_12::regions::run_outlines()),
+        _ => print!("{}", day_12::regions::run()),
+    }
+}
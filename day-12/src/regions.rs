@@ -0,0 +1,467 @@
+use std::collections::BTreeSet;
+
+use grid::{Direction, Grid, Vec2};
+
+const INPUT: &str = include_str!("input.txt");
+
+const DIRECTIONS: [grid::Direction; 4] = [Direction::N, Direction::E, Direction::S, Direction::W];
+
+// the way each fence runs, clockwise around the region, so the region is
+// always on the right. Turning left is the previous step, right the next one.
+const STEPS: [(i32, i32); 4] = [(1, 0), (0, 1), (-1, 0), (0, -1)];
+
+pub trait GridExt {
+    fn get_neighbours(&self, point: &Vec2) -> Neighbours<'_>;
+}
+
+impl GridExt for Grid<'_> {
+    #[inline]
+    fn get_neighbours(&self, point: &Vec2) -> Neighbours<'_> {
+        Neighbours {
+            grid: self,
+            point: point.clone(),
+            direction_index: 0,
+        }
+    }
+}
+
+pub struct Neighbours<'a> {
+    grid: &'a Grid<'a>,
+    point: Vec2,
+    direction_index: usize,
+}
+
+impl Iterator for Neighbours<'_> {
+    type Item = (Vec2, char);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.direction_index < DIRECTIONS.len() {
+            let point = &self.point + DIRECTIONS[self.direction_index];
+            self.direction_index += 1;
+            if let Some(c) = self.grid.get(&point) {
+                return Some((point, c));
+            }
+        }
+        None
+    }
+}
+
+// a closed fence, as the corners between plots that it turns at. Plot (x, y)
+// is the square from (x, y) to (x + 1, y + 1).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outline {
+    pub vertices: Vec<Vec2>,
+}
+
+impl Outline {
+    // twice the area inside the outline, positive when it runs clockwise on
+    // the map (y going down) around the region, negative around a hole
+    pub fn signed_area(&self) -> i64 {
+        let n = self.vertices.len();
+        (0..n)
+            .map(|i| {
+                let (a, b) = (&self.vertices[i], &self.vertices[(i + 1) % n]);
+                a.x as i64 * b.y as i64 - b.x as i64 * a.y as i64
+            })
+            .sum()
+    }
+
+    pub fn is_hole(&self) -> bool {
+        self.signed_area() < 0
+    }
+
+    // each corner starts a new side
+    pub fn sides(&self) -> usize {
+        self.vertices.len()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Region {
+    pub plant: char,
+    // in reading order
+    pub plots: Vec<Vec2>,
+    pub perimeter: usize,
+    // the outside of the region first, then its holes
+    pub outlines: Vec<Outline>,
+}
+
+impl Region {
+    pub fn area(&self) -> usize {
+        self.plots.len()
+    }
+
+    pub fn sides(&self) -> usize {
+        self.outlines.iter().map(Outline::sides).sum()
+    }
+
+    // the other regions (or groups of them) that this one goes all the way
+    // around
+    pub fn holes(&self) -> usize {
+        self.outlines
+            .iter()
+            .filter(|outline| outline.is_hole())
+            .count()
+    }
+}
+
+// what a region's fence costs
+pub trait PricingModel {
+    fn price(&self, region: &Region) -> usize;
+}
+
+// area x perimeter, like part 1
+#[derive(Debug)]
+pub struct Perimeter;
+
+impl PricingModel for Perimeter {
+    fn price(&self, region: &Region) -> usize {
+        region.area() * region.perimeter
+    }
+}
+
+// area x sides, the bulk discount from part 2
+#[derive(Debug)]
+pub struct Sides;
+
+impl PricingModel for Sides {
+    fn price(&self, region: &Region) -> usize {
+        region.area() * region.sides()
+    }
+}
+
+impl<F: Fn(&Region) -> usize> PricingModel for F {
+    fn price(&self, region: &Region) -> usize {
+        self(region)
+    }
+}
+
+#[derive(Debug)]
+pub struct Garden {
+    // in reading order of their first plot
+    pub regions: Vec<Region>,
+}
+
+impl Garden {
+    pub fn parse(input: &str) -> Self {
+        let grid = Grid::new(input);
+        let (rows, cols) = (input.lines().count(), grid.cols);
+
+        let mut seen = vec![false; rows * cols];
+        let mut regions = vec![];
+        let mut queue: Vec<Vec2> = vec![];
+        for y in 0..rows {
+            for x in 0..cols {
+                if seen[y * cols + x] {
+                    continue;
+                }
+                seen[y * cols + x] = true;
+                let start = Vec2::new(x as i32, y as i32);
+                let plant = grid.get(&start).expect("plot should be on the map");
+
+                let mut plots = vec![];
+                queue.push(start);
+                while let Some(point) = queue.pop() {
+                    for (neighbour, c) in grid.get_neighbours(&point) {
+                        let index = neighbour.y as usize * cols + neighbour.x as usize;
+                        if c == plant && !seen[index] {
+                            seen[index] = true;
+                            queue.push(neighbour);
+                        }
+                    }
+                    plots.push(point);
+                }
+                plots.sort_unstable_by_key(|plot| (plot.y, plot.x));
+
+                regions.push(Region::new(&grid, plant, plots));
+            }
+        }
+
+        Self { regions }
+    }
+
+    pub fn price(&self, model: &dyn PricingModel) -> usize {
+        self.regions.iter().map(|region| model.price(region)).sum()
+    }
+}
+
+impl Region {
+    fn new(grid: &Grid, plant: char, plots: Vec<Vec2>) -> Self {
+        // every side of a plot that isn't next to the same plant has a fence
+        // along it, as (y, x, step) so the top left one comes first
+        let mut fences = BTreeSet::new();
+        for plot in &plots {
+            let (x, y) = (plot.x, plot.y);
+            let starts = [(x, y), (x + 1, y), (x + 1, y + 1), (x, y + 1)];
+            for (step, direction) in DIRECTIONS.into_iter().enumerate() {
+                if grid.get(&(plot + direction)) != Some(plant) {
+                    let (x, y) = starts[step];
+                    fences.insert((y, x, step));
+                }
+            }
+        }
+        let perimeter = fences.len();
+
+        let mut outlines = vec![];
+        while let Some(first) = fences.pop_first() {
+            let mut steps = vec![first];
+            loop {
+                let (y, x, step) = *steps.last().unwrap();
+                let (dx, dy) = STEPS[step];
+                let (x, y) = (x + dx, y + dy);
+                // where two plots of the region only touch at a corner there
+                // are two ways on, turning left keeps to the plots of the
+                // other region, so each region it surrounds is its own hole
+                let next = [(step + 3) % 4, step, (step + 1) % 4]
+                    .into_iter()
+                    .map(|step| (y, x, step))
+                    .find(|fence| *fence == first || fences.contains(fence))
+                    .expect("fences should join up");
+                if next == first {
+                    break;
+                }
+                fences.remove(&next);
+                steps.push(next);
+            }
+
+            let n = steps.len();
+            let vertices = (0..n)
+                .filter(|&i| steps[i].2 != steps[(i + n - 1) % n].2)
+                .map(|i| Vec2::new(steps[i].1, steps[i].0))
+                .collect();
+            outlines.push(Outline { vertices });
+        }
+
+        Self {
+            plant,
+            plots,
+            perimeter,
+            outlines,
+        }
+    }
+}
+
+#[tracing::instrument(level = "trace", skip())]
+pub fn run() -> String {
+    process(INPUT)
+}
+
+#[tracing::instrument(level = "trace", skip())]
+pub fn run_outlines() -> String {
+    outlines(INPUT)
+}
+
+#[tracing::instrument(level = "trace", skip(input))]
+fn process(input: &str) -> String {
+    let garden = Garden::parse(input);
+
+    let mut report = String::new();
+    for region in &garden.regions {
+        report.push_str(&format!(
+            "{} at {},{}: area {}, perimeter {}, sides {}, holes {}\n",
+            region.plant,
+            region.plots[0].x,
+            region.plots[0].y,
+            region.area(),
+            region.perimeter,
+            region.sides(),
+            region.holes()
+        ));
+    }
+    report.push_str(&format!(
+        "price by perimeter {}, by sides {}\n",
+        garden.price(&Perimeter),
+        garden.price(&Sides)
+    ));
+    report
+}
+
+// one outline a line, `A outer 0,0 4,0 4,1 0,1`
+#[tracing::instrument(level = "trace", skip(input))]
+fn outlines(input: &str) -> String {
+    let mut out = String::new();
+    for region in Garden::parse(input).regions {
+        for outline in &region.outlines {
+            out.push(region.plant);
+            out.push_str(match outline.is_hole() {
+                true => " hole",
+                false => " outer",
+            });
+            for vertex in &outline.vertices {
+                out.push_str(&format!(" {},{}", vertex.x, vertex.y));
+            }
+            out.push('\n');
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "AAAA
+BBCD
+BBCC
+EEEC";
+
+    #[test]
+    fn it_works() {
+        assert_eq!(
+            process(EXAMPLE),
+            "A at 0,0: area 4, perimeter 10, sides 4, holes 0
+B at 0,1: area 4, perimeter 8, sides 4, holes 0
+C at 2,1: area 4, perimeter 10, sides 8, holes 0
+D at 3,1: area 1, perimeter 4, sides 4, holes 0
+E at 0,3: area 3, perimeter 8, sides 4, holes 0
+price by perimeter 140, by sides 80
+"
+        );
+    }
+
+    #[test]
+    fn test_outlines() {
+        assert_eq!(
+            outlines(
+                "OOOOO
+OXOXO
+OOOOO
+OXOXO
+OOOOO"
+            )
+            .lines()
+            .take(3)
+            .collect::<Vec<_>>(),
+            [
+                "O outer 0,0 5,0 5,5 0,5",
+                "O hole 1,1 1,2 2,2 2,1",
+                "O hole 3,1 3,2 4,2 4,1",
+            ]
+        );
+        assert_eq!(
+            outlines("AA\nAB").lines().last(),
+            Some("B outer 1,1 2,1 2,2 1,2")
+        );
+    }
+
+    #[test]
+    fn test_holes() {
+        // the two B regions only touch at a corner, so they're separate holes
+        let garden = Garden::parse(
+            "AAAAAA
+AAABBA
+AAABBA
+ABBAAA
+ABBAAA
+AAAAAA",
+        );
+        let a = &garden.regions[0];
+        assert_eq!(
+            (a.area(), a.perimeter, a.sides(), a.holes()),
+            (28, 40, 12, 2)
+        );
+        assert_eq!(a.outlines.len(), 3);
+        assert_eq!(a.outlines[0].signed_area(), 2 * 36);
+        assert!(a.outlines[1..].iter().all(|hole| hole.signed_area() == -8));
+
+        // the corner between the ends of the ring leaves the middle shut in
+        let garden = Garden::parse(
+            "BAA
+ABA
+AAA",
+        );
+        let a = &garden.regions[1];
+        assert_eq!((a.plant, a.area(), a.holes()), ('A', 7, 1));
+        assert_eq!(a.sides(), 6 + 4);
+    }
+
+    #[test]
+    fn test_pricing_models() {
+        let garden = Garden::parse(
+            "EEEEE
+EXXXX
+EEEEE
+EXXXX
+EEEEE",
+        );
+        assert_eq!(garden.price(&Perimeter), 692);
+        assert_eq!(garden.price(&Sides), 236);
+        let per_hole = |region: &Region| region.area() * (1 + region.holes());
+        assert_eq!(garden.price(&per_hole), 25);
+    }
+
+    // the number of corners around each plot, which is how part 2 used to
+    // count sides
+    fn corners(grid: &Grid, point: &Vec2) -> usize {
+        let center = &grid.get(point);
+        let around = |direction| grid.get(&(point + direction));
+        let (nw, n, ne, e) = (
+            around(Direction::NW),
+            around(Direction::N),
+            around(Direction::NE),
+            around(Direction::E),
+        );
+        let (se, s, sw, w) = (
+            around(Direction::SE),
+            around(Direction::S),
+            around(Direction::SW),
+            around(Direction::W),
+        );
+
+        [(nw, n, w), (ne, n, e), (se, s, e), (sw, s, w)]
+            .iter()
+            .filter(|(a, b, c)| {
+                let concave = center != a && center == b && center == c;
+                let convex = center != a && center != b && center != c;
+                let mirrored_convex = center == a && center != b && center != c;
+                concave || convex || mirrored_convex
+            })
+            .count()
+    }
+
+    #[test]
+    fn test_matches_corners() {
+        let mut state: u64 = 2024;
+        for plants in [2, 3, 5] {
+            let input: String = (0..12)
+                .map(|_| {
+                    (0..12)
+                        .map(|_| {
+                            // xorshift64
+                            state ^= state << 13;
+                            state ^= state >> 7;
+                            state ^= state << 17;
+                            char::from(b'A' + (state % plants) as u8)
+                        })
+                        .chain(['\n'])
+                        .collect::<String>()
+                })
+                .collect();
+
+            let grid = Grid::new(&input);
+            for region in Garden::parse(&input).regions {
+                let corners: usize = region.plots.iter().map(|plot| corners(&grid, plot)).sum();
+                assert_eq!(region.sides(), corners, "{input}");
+                assert_eq!(
+                    region.outlines.iter().filter(|o| !o.is_hole()).count(),
+                    1,
+                    "{input}"
+                );
+                let area: i64 = region.outlines.iter().map(Outline::signed_area).sum();
+                assert_eq!(area, 2 * region.area() as i64, "{input}");
+            }
+        }
+    }
+}
+
+#[cfg(feature = "bench")]
+pub mod benchmarks {
+    use super::{Garden, Perimeter, Sides, INPUT};
+
+    #[divan::bench()]
+    fn bench_garden() {
+        let garden = Garden::parse(INPUT);
+        garden.price(&Perimeter);
+        garden.price(&Sides);
+    }
+}
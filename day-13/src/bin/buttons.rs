@@ -0,0 +1,14 @@
+// prints the cheapest presses for every machine in a file of machine
+// descriptions, which can have any number of buttons with their own costs and
+// limits, like `cargo run -p day-13 --bin buttons -- machines.txt`. Without a
+// file it solves the puzzle input.
+fn main() {
+    match std::env::args().nth(1) {
+        Some(path) => {
+            let input = std::fs::read_to_string(&path)
+                .unwrap_or_else(|error| panic!("can't read {path}: {error}"));
+            print!("{}", day_13::buttons::run_with(&input));
+        }
+        None => print!("{}", day_13::buttons::run()),
+    }
+}
@@ -0,0 +1,9 @@
+// prints the cheapest presses for every claw machine, with the prizes moved
+// by an offset like `cargo run -p day-13 --bin claw -- 10000000000000`
+fn main() {
+    let offset = std::env::args()
+        .nth(1)
+        .map(|offset| offset.parse().expect("offset should be a number"))
+        .unwrap_or(0);
+    print!("{}", day_13::claw::run(offset));
+}
@@ -0,0 +1,767 @@
+use std::fmt;
+
+use glam::I64Vec2;
+
+use crate::claw::{ClawMachine, Overflow};
+
+const INPUT: &str = include_str!("input.txt");
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SolveError {
+    Overflow(Overflow),
+    // the presses could grow forever without moving the claw, so the search
+    // might never end
+    Unbounded,
+}
+
+impl fmt::Display for SolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SolveError::Overflow(overflow) => write!(f, "{overflow}"),
+            SolveError::Unbounded => write!(f, "buttons cancel each other out without a limit"),
+        }
+    }
+}
+
+impl std::error::Error for SolveError {}
+
+impl From<Overflow> for SolveError {
+    fn from(overflow: Overflow) -> Self {
+        SolveError::Overflow(overflow)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    // a line without a label before its fields
+    Line(String),
+    Field(String),
+    // a button or prize without both an x and a y
+    Position(String),
+    // a button that costs nothing to press
+    Free(String),
+    NoPrize,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Line(line) => write!(f, "line `{line}` should have a label"),
+            ParseError::Field(field) => write!(f, "can't read field `{field}`"),
+            ParseError::Position(label) => write!(f, "{label} should have an x and a y"),
+            ParseError::Free(name) => write!(f, "pressing button {name} should cost something"),
+            ParseError::NoPrize => write!(f, "machine should have a prize"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Button {
+    pub name: String,
+    pub step: I64Vec2,
+    // tokens for each press, more than zero
+    pub cost: u32,
+    // the most times it can be pressed
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Machine {
+    pub buttons: Vec<Button>,
+    pub prize: I64Vec2,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Solution {
+    // for each button, in order
+    pub presses: Vec<i128>,
+    pub tokens: i128,
+}
+
+impl From<ClawMachine> for Machine {
+    fn from(ClawMachine { a, b, prize }: ClawMachine) -> Self {
+        let button = |name: &str, step, cost| Button {
+            name: name.to_string(),
+            step,
+            cost,
+            limit: None,
+        };
+        Self {
+            buttons: vec![button("A", a, 3), button("B", b, 1)],
+            prize,
+        }
+    }
+}
+
+// the puzzle's machines, with any number of buttons that can also move the
+// claw back and have their own cost and limit:
+//
+//   Button A: X+94, Y+34
+//   Button C: X-3, Y+2, cost 2, limit 100
+//   Prize: X=8400, Y=5400
+//
+// Button A costs 3 tokens and the rest 1 unless they say otherwise
+pub fn parse(input: &str) -> Result<Vec<Machine>, ParseError> {
+    input
+        .trim()
+        .split("\n\n")
+        .filter(|chunk| !chunk.is_empty())
+        .map(Machine::parse)
+        .collect()
+}
+
+fn number<T: std::str::FromStr>(value: &str, field: &str) -> Result<T, ParseError> {
+    value
+        .trim_start_matches(['+', '=', ' '])
+        .parse()
+        .map_err(|_| ParseError::Field(field.to_string()))
+}
+
+impl Machine {
+    pub fn parse(chunk: &str) -> Result<Self, ParseError> {
+        let mut buttons = vec![];
+        let mut prize = None;
+        for line in chunk.lines() {
+            let (label, fields) = line
+                .split_once(": ")
+                .ok_or_else(|| ParseError::Line(line.to_string()))?;
+            let mut x = None;
+            let mut y = None;
+            let mut cost = None;
+            let mut limit = None;
+            for field in fields.split(", ") {
+                match field.split_at_checked(1) {
+                    Some(("X", n)) => x = Some(number(n, field)?),
+                    Some(("Y", n)) => y = Some(number(n, field)?),
+                    _ => match field.split_once(' ') {
+                        Some(("cost", n)) => cost = Some(number(n, field)?),
+                        Some(("limit", n)) => limit = Some(number(n, field)?),
+                        _ => return Err(ParseError::Field(field.to_string())),
+                    },
+                }
+            }
+            let (Some(x), Some(y)) = (x, y) else {
+                return Err(ParseError::Position(label.to_string()));
+            };
+            let step = I64Vec2::new(x, y);
+
+            match label.strip_prefix("Button ") {
+                Some(name) => {
+                    let cost = cost.unwrap_or(if name == "A" { 3 } else { 1 });
+                    if cost == 0 {
+                        return Err(ParseError::Free(name.to_string()));
+                    }
+                    buttons.push(Button {
+                        name: name.to_string(),
+                        step,
+                        cost,
+                        limit,
+                    });
+                }
+                None => prize = Some(step),
+            }
+        }
+
+        Ok(Self {
+            buttons,
+            prize: prize.ok_or(ParseError::NoPrize)?,
+        })
+    }
+
+    // limits every button that doesn't have a limit of its own
+    pub fn with_limit(mut self, presses: u32) -> Self {
+        for button in &mut self.buttons {
+            button.limit = button.limit.or(Some(presses));
+        }
+        self
+    }
+
+    pub fn with_offset(mut self, offset: i64) -> Self {
+        self.prize = I64Vec2::new(
+            self.prize
+                .x
+                .checked_add(offset)
+                .expect("prize should fit in an i64"),
+            self.prize
+                .y
+                .checked_add(offset)
+                .expect("prize should fit in an i64"),
+        );
+        self
+    }
+
+    // the cheapest presses that win the prize, `None` when it can't be won.
+    //
+    // This is the integer program: minimise the sum of cost * presses, where
+    // the presses move the claw onto the prize and are between 0 and their
+    // limits. It's solved by branch and bound, splitting on a button whose
+    // presses aren't whole in the best fractional answer. Fractional answers
+    // are found by trying every vertex of the region of allowed presses, so
+    // it's meant for a handful of buttons.
+    pub fn solve(&self) -> Result<Option<Solution>, SolveError> {
+        let program = Program::new(self)?;
+        if program.is_unbounded() {
+            return Err(SolveError::Unbounded);
+        }
+
+        let n = self.buttons.len();
+        let mut best: Option<Solution> = None;
+        let mut stack = vec![(vec![0; n], program.limits.clone())];
+        while let Some((low, high)) = stack.pop() {
+            let Some(vertex) = program.relax(&low, &high)? else {
+                continue;
+            };
+            // costs are whole, so a fractional bound rounds up
+            let bound = div_ceil(vertex.tokens.0, vertex.tokens.1);
+            if best.as_ref().is_some_and(|best| bound >= best.tokens) {
+                continue;
+            }
+
+            match vertex.presses.iter().position(|&(num, den)| num % den != 0) {
+                None => {
+                    best = Some(Solution {
+                        presses: vertex.presses.iter().map(|&(num, den)| num / den).collect(),
+                        tokens: bound,
+                    })
+                }
+                Some(i) => {
+                    let (num, den) = vertex.presses[i];
+                    let (mut below, mut above) = (high.clone(), low.clone());
+                    below[i] = Some(div_floor(num, den));
+                    above[i] = div_ceil(num, den);
+                    stack.push((above, high));
+                    stack.push((low, below));
+                }
+            }
+        }
+        Ok(best)
+    }
+}
+
+// a fractional answer, each number as (numerator, denominator)
+#[derive(Debug)]
+struct Vertex {
+    presses: Vec<(i128, i128)>,
+    tokens: (i128, i128),
+}
+
+#[derive(Debug)]
+struct Program {
+    // the independent rows of the steps and the prize, one or two of them
+    rows: Vec<(Vec<i128>, i128)>,
+    costs: Vec<i128>,
+    limits: Vec<Option<i128>>,
+}
+
+impl Program {
+    fn new(machine: &Machine) -> Result<Self, Overflow> {
+        let xs: Vec<i128> = machine.buttons.iter().map(|b| b.step.x as i128).collect();
+        let ys: Vec<i128> = machine.buttons.iter().map(|b| b.step.y as i128).collect();
+        let x_row = (xs, machine.prize.x as i128);
+        let y_row = (ys, machine.prize.y as i128);
+
+        // the y row only adds something if it isn't a multiple of the x row
+        let mut columns: Vec<(i128, i128)> = x_row
+            .0
+            .iter()
+            .copied()
+            .zip(y_row.0.iter().copied())
+            .collect();
+        columns.push((x_row.1, y_row.1));
+        let mut independent = false;
+        for (i, a) in columns.iter().enumerate() {
+            for b in &columns[i + 1..] {
+                independent |= cross(a.0, a.1, b.0, b.1)? != 0;
+            }
+        }
+        let zero = |row: &(Vec<i128>, i128)| row.0.iter().all(|&v| v == 0) && row.1 == 0;
+        let rows = match (zero(&x_row), zero(&y_row), independent) {
+            (true, true, _) => vec![],
+            (_, _, true) => vec![x_row, y_row],
+            (true, false, false) => vec![y_row],
+            (false, _, false) => vec![x_row],
+        };
+
+        Ok(Self {
+            rows,
+            costs: machine.buttons.iter().map(|b| b.cost as i128).collect(),
+            limits: machine
+                .buttons
+                .iter()
+                .map(|b| b.limit.map(i128::from))
+                .collect(),
+        })
+    }
+
+    // whether the presses of unlimited buttons can grow forever while the
+    // claw stays where it is: some of them, at most one more than there are
+    // rows, can be pressed in proportion to cancel each other out. Buttons
+    // that don't move the claw are never worth pressing, so they don't count.
+    fn is_unbounded(&self) -> bool {
+        let unlimited: Vec<usize> = (0..self.costs.len())
+            .filter(|&i| self.limits[i].is_none())
+            .filter(|&i| self.rows.iter().any(|row| row.0[i] != 0))
+            .collect();
+        let column = |i: usize| -> Vec<i128> { self.rows.iter().map(|row| row.0[i]).collect() };
+        (2..=self.rows.len() + 1).any(|size| {
+            subsets(&unlimited, size).any(|subset| {
+                let columns: Vec<Vec<i128>> = subset.iter().map(|&i| column(i)).collect();
+                kernel(&columns).is_some_and(|kernel| {
+                    kernel.iter().all(|&k| k >= 0) || kernel.iter().all(|&k| k <= 0)
+                })
+            })
+        })
+    }
+
+    // the cheapest fractional presses between `low` and `high`, `None` when
+    // there aren't any. The cheapest is at a vertex, where all but as many
+    // buttons as there are rows are at one of their bounds.
+    fn relax(&self, low: &[i128], high: &[Option<i128>]) -> Result<Option<Vertex>, Overflow> {
+        let n = self.costs.len();
+        if (0..n).any(|i| high[i].is_some_and(|high| high < low[i])) {
+            return Ok(None);
+        }
+
+        let indices: Vec<usize> = (0..n).collect();
+        let mut best: Option<Vertex> = None;
+        for basis in subsets(&indices, self.rows.len()) {
+            let columns: Vec<Vec<i128>> = basis
+                .iter()
+                .map(|&i| self.rows.iter().map(|row| row.0[i]).collect())
+                .collect();
+            let determinant = determinant(&columns)?;
+            if determinant == 0 {
+                continue;
+            }
+
+            let others: Vec<usize> = (0..n).filter(|i| !basis.contains(i)).collect();
+            // every other button at its low or high bound, high only if it
+            // has one
+            let choices = others.iter().map(|&i| match high[i] {
+                Some(high) if high != low[i] => 2,
+                _ => 1,
+            });
+            let combinations: usize = choices.clone().product();
+            for mut combination in 0..combinations {
+                let mut presses = vec![(0, 1); n];
+                for (&i, choices) in others.iter().zip(choices.clone()) {
+                    let value = match combination % choices {
+                        0 => low[i],
+                        _ => high[i].unwrap(),
+                    };
+                    combination /= choices;
+                    presses[i] = (value, 1);
+                }
+
+                // what's left for the basis to cover
+                let mut rhs = vec![];
+                for row in &self.rows {
+                    let mut left = row.1;
+                    for &i in &others {
+                        left = sub(left, mul(row.0[i], presses[i].0)?)?;
+                    }
+                    rhs.push(left);
+                }
+                let numerators = solve(&columns, &rhs)?;
+
+                let mut feasible = true;
+                for (&i, &num) in basis.iter().zip(&numerators) {
+                    // num / determinant against the bounds, with a positive
+                    // denominator
+                    let (num, den) = match determinant < 0 {
+                        true => (-num, -determinant),
+                        false => (num, determinant),
+                    };
+                    feasible &= num >= mul(low[i], den)?;
+                    if let Some(high) = high[i] {
+                        feasible &= num <= mul(high, den)?;
+                    }
+                    presses[i] = (num, den);
+                }
+                if !feasible {
+                    continue;
+                }
+
+                let tokens = total(&self.costs, &presses)?;
+                let cheaper = match &best {
+                    Some(best) => mul(tokens.0, best.tokens.1)? < mul(best.tokens.0, tokens.1)?,
+                    None => true,
+                };
+                if cheaper {
+                    best = Some(Vertex { presses, tokens });
+                }
+            }
+        }
+
+        Ok(best)
+    }
+}
+
+// the sum of cost * presses over a common denominator
+fn total(costs: &[i128], presses: &[(i128, i128)]) -> Result<(i128, i128), Overflow> {
+    let den = presses.iter().map(|&(_, den)| den).max().unwrap_or(1);
+    let mut num = 0;
+    for (&cost, &(n, d)) in costs.iter().zip(presses) {
+        // denominators are either 1 or the same determinant
+        let n = match d {
+            1 => mul(n, den)?,
+            _ => n,
+        };
+        num = add(num, mul(cost, n)?)?;
+    }
+    Ok((num, den))
+}
+
+// every way to pick `k` of `items`, in order
+fn subsets(items: &[usize], k: usize) -> impl Iterator<Item = Vec<usize>> + '_ {
+    let n = items.len();
+    (0u64..1 << n)
+        .filter(move |mask| mask.count_ones() as usize == k)
+        .map(move |mask| {
+            (0..n)
+                .filter(|i| mask >> i & 1 == 1)
+                .map(|i| items[i])
+                .collect()
+        })
+}
+
+// of one or two columns of one or two rows
+fn determinant(columns: &[Vec<i128>]) -> Result<i128, Overflow> {
+    match columns {
+        [] => Ok(1),
+        [a] => Ok(a[0]),
+        [a, b] => cross(a[0], a[1], b[0], b[1]),
+        _ => unreachable!("there are at most two rows"),
+    }
+}
+
+// the numerators over `determinant` of the basis presses, by Cramer's rule
+fn solve(columns: &[Vec<i128>], rhs: &[i128]) -> Result<Vec<i128>, Overflow> {
+    match columns {
+        [] => Ok(vec![]),
+        [_] => Ok(vec![rhs[0]]),
+        [a, b] => Ok(vec![
+            cross(rhs[0], rhs[1], b[0], b[1])?,
+            cross(a[0], a[1], rhs[0], rhs[1])?,
+        ]),
+        _ => unreachable!("there are at most two rows"),
+    }
+}
+
+// the combination of the columns that adds up to nothing, if they're just
+// dependent enough for there to be only one (up to scale)
+fn kernel(columns: &[Vec<i128>]) -> Option<Vec<i128>> {
+    let k = match columns {
+        [a, b] => {
+            let r = (0..a.len()).find(|&r| a[r] != 0 || b[r] != 0)?;
+            let k = vec![b[r], -a[r]];
+            (0..a.len())
+                .all(|r| a[r] * k[0] + b[r] * k[1] == 0)
+                .then_some(k)?
+        }
+        [a, b, c] if a.len() == 2 => vec![
+            b[0] * c[1] - b[1] * c[0],
+            c[0] * a[1] - c[1] * a[0],
+            a[0] * b[1] - a[1] * b[0],
+        ],
+        _ => return None,
+    };
+    Some(k).filter(|k| k.iter().any(|&v| v != 0))
+}
+
+fn add(a: i128, b: i128) -> Result<i128, Overflow> {
+    a.checked_add(b).ok_or(Overflow)
+}
+
+fn sub(a: i128, b: i128) -> Result<i128, Overflow> {
+    a.checked_sub(b).ok_or(Overflow)
+}
+
+fn mul(a: i128, b: i128) -> Result<i128, Overflow> {
+    a.checked_mul(b).ok_or(Overflow)
+}
+
+fn cross(ax: i128, ay: i128, bx: i128, by: i128) -> Result<i128, Overflow> {
+    sub(mul(ax, by)?, mul(ay, bx)?)
+}
+
+fn div_floor(n: i128, d: i128) -> i128 {
+    n.div_euclid(d)
+}
+
+fn div_ceil(n: i128, d: i128) -> i128 {
+    -(-n).div_euclid(d)
+}
+
+#[tracing::instrument(level = "trace", skip())]
+pub fn run() -> String {
+    process(INPUT)
+}
+
+// for machine descriptions that aren't the puzzle input
+#[tracing::instrument(level = "trace", skip(input))]
+pub fn run_with(input: &str) -> String {
+    process(input)
+}
+
+#[tracing::instrument(level = "trace", skip(input))]
+fn process(input: &str) -> String {
+    let machines = match parse(input) {
+        Ok(machines) => machines,
+        Err(error) => return format!("{error}\n"),
+    };
+    let mut report = String::new();
+    for (i, machine) in machines.into_iter().enumerate() {
+        let line = match machine.solve() {
+            Ok(Some(Solution { presses, tokens })) => {
+                let presses: Vec<String> = machine
+                    .buttons
+                    .iter()
+                    .zip(presses)
+                    .map(|(button, presses)| format!("{presses} {}", button.name))
+                    .collect();
+                format!("{} for {tokens} tokens", presses.join(", "))
+            }
+            Ok(None) => "no prize".to_string(),
+            Err(error) => error.to_string(),
+        };
+        report.push_str(&format!("machine {}: {line}\n", i + 1));
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::claw::Solver;
+
+    const EXAMPLE: &str = "Button A: X+94, Y+34
+Button B: X+22, Y+67
+Prize: X=8400, Y=5400
+
+Button A: X+26, Y+66
+Button B: X+67, Y+21
+Prize: X=12748, Y=12176
+
+Button A: X+17, Y+86
+Button B: X+84, Y+37
+Prize: X=7870, Y=6450
+
+Button A: X+69, Y+23
+Button B: X+27, Y+71
+Prize: X=18641, Y=10279";
+
+    #[test]
+    fn it_works() {
+        assert_eq!(
+            process(EXAMPLE),
+            "machine 1: 80 A, 40 B for 280 tokens
+machine 2: no prize
+machine 3: 38 A, 86 B for 200 tokens
+machine 4: no prize
+"
+        );
+        let tokens: i128 = parse(EXAMPLE)
+            .unwrap()
+            .into_iter()
+            .filter_map(|machine| machine.with_offset(10000000000000).solve().unwrap())
+            .map(|solution| solution.tokens)
+            .sum();
+        assert_eq!(tokens, 875318608908);
+    }
+
+    #[test]
+    fn test_parse() {
+        let machine = Machine::parse(
+            "Button A: X+4, Y+0
+Button B: X+0, Y+4
+Button C: X-3, Y+2, cost 2, limit 7
+Prize: X=5, Y=6",
+        )
+        .unwrap();
+        assert_eq!(machine.buttons.len(), 3);
+        assert_eq!(machine.buttons[0].cost, 3);
+        assert_eq!(machine.buttons[1].cost, 1);
+        assert_eq!(
+            machine.buttons[2],
+            Button {
+                name: "C".to_string(),
+                step: I64Vec2::new(-3, 2),
+                cost: 2,
+                limit: Some(7),
+            }
+        );
+        assert_eq!(machine.prize, I64Vec2::new(5, 6));
+        assert_eq!(machine.clone().with_limit(100).buttons[1].limit, Some(100));
+        assert_eq!(machine.with_limit(100).buttons[2].limit, Some(7));
+
+        for (chunk, error) in [
+            (
+                "Button A X+1, Y+1",
+                ParseError::Line("Button A X+1, Y+1".to_string()),
+            ),
+            ("Button A: X+1, Y+1, ", ParseError::Field("".to_string())),
+            ("Button A: X+1,  Y+1", ParseError::Field(" Y+1".to_string())),
+            ("Button A: X+a, Y+1", ParseError::Field("X+a".to_string())),
+            ("Button A: Y+1, cost", ParseError::Field("cost".to_string())),
+            (
+                "Button A: X+1, Y+1, limit -1",
+                ParseError::Field("limit -1".to_string()),
+            ),
+            (
+                "Button A: X+1, Y+1, cost 0",
+                ParseError::Free("A".to_string()),
+            ),
+            (
+                "Button A: X+1",
+                ParseError::Position("Button A".to_string()),
+            ),
+            ("Button A: X+1, Y+1", ParseError::NoPrize),
+            ("", ParseError::NoPrize),
+        ] {
+            assert_eq!(Machine::parse(chunk), Err(error));
+        }
+        assert_eq!(parse(" \n"), Ok(vec![]));
+        assert_eq!(
+            run_with("Button A: X+1, Y+1,\nPrize: X=1, Y=1"),
+            "can't read field `Y+1,`\n"
+        );
+    }
+
+    #[test]
+    fn test_three_buttons() {
+        // C gets the claw back to x = 5, which A and B can't reach on their own
+        assert_eq!(
+            run_with(
+                "Button A: X+4, Y+0
+Button B: X+0, Y+4
+Button C: X-3, Y+2, cost 2, limit 7
+Prize: X=5, Y=6"
+            ),
+            "machine 1: 2 A, 1 B, 1 C for 9 tokens\n"
+        );
+        // two of the cheap C together beat one A
+        assert_eq!(
+            run_with(
+                "Button A: X+2, Y+2, cost 5
+Button B: X+1, Y+0
+Button C: X+1, Y+1, cost 2
+Prize: X=12, Y=10"
+            ),
+            "machine 1: 0 A, 2 B, 10 C for 22 tokens\n"
+        );
+        // the limit on C makes A worth it
+        assert_eq!(
+            run_with(
+                "Button A: X+2, Y+2, cost 5
+Button B: X+1, Y+0
+Button C: X+1, Y+1, cost 2, limit 3
+Prize: X=12, Y=10"
+            ),
+            "machine 1: 4 A, 2 B, 2 C for 26 tokens\n"
+        );
+    }
+
+    #[test]
+    fn test_unbounded() {
+        let machine = Machine::parse(
+            "Button A: X+1, Y+1
+Button B: X-2, Y-2
+Button C: X+0, Y+1
+Prize: X=3, Y=4",
+        )
+        .unwrap();
+        assert_eq!(machine.solve(), Err(SolveError::Unbounded));
+        // with a limit on either of the buttons that cancel out it's fine
+        let solution = machine.with_limit(10).solve().unwrap().unwrap();
+        assert_eq!((solution.presses, solution.tokens), (vec![3, 0, 1], 10));
+    }
+
+    // the cheapest presses by trying every combination
+    fn search(machine: &Machine, limit: i64) -> Option<i128> {
+        let mut best = None;
+        let mut presses = vec![0; machine.buttons.len()];
+        loop {
+            let claw: I64Vec2 = machine
+                .buttons
+                .iter()
+                .zip(&presses)
+                .map(|(button, &n)| button.step * n)
+                .sum();
+            if claw == machine.prize {
+                let tokens: i128 = machine
+                    .buttons
+                    .iter()
+                    .zip(&presses)
+                    .map(|(button, &n)| button.cost as i128 * n as i128)
+                    .sum();
+                best = Some(best.map_or(tokens, |best: i128| best.min(tokens)));
+            }
+
+            let Some(i) = presses.iter().position(|&n| n < limit) else {
+                return best;
+            };
+            presses[..i].fill(0);
+            presses[i] += 1;
+        }
+    }
+
+    #[test]
+    fn test_matches_search() {
+        let mut state: u64 = 7;
+        let mut random = |n: u64| {
+            // xorshift64
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state % n) as i64
+        };
+        for round in 0..150 {
+            let buttons: Vec<Button> = (0..2 + round % 2)
+                .map(|i| Button {
+                    name: ((b'A' + i as u8) as char).to_string(),
+                    step: I64Vec2::new(random(9) - 2, random(9) - 2),
+                    cost: 1 + random(4) as u32,
+                    limit: Some(12),
+                })
+                .collect();
+            let prize = buttons
+                .iter()
+                .map(|button| button.step * random(8))
+                .sum::<I64Vec2>()
+                + I64Vec2::new(random(2), 0);
+            let machine = Machine { buttons, prize };
+
+            let tokens = machine.solve().unwrap().map(|solution| solution.tokens);
+            assert_eq!(tokens, search(&machine, 12), "{machine:?}");
+        }
+    }
+
+    #[test]
+    fn test_matches_claw() {
+        let solver = Solver::default().with_offset(10000000000000);
+        for claw in crate::claw::parse(EXAMPLE) {
+            let machine = Machine::from(claw).with_offset(10000000000000);
+            let expected = solver.solve(&claw).unwrap();
+            let solution = machine.solve().unwrap();
+            assert_eq!(
+                solution.map(|solution| (solution.presses, solution.tokens)),
+                expected.map(|presses| (vec![presses.a, presses.b], presses.tokens))
+            );
+        }
+    }
+}
+
+#[cfg(feature = "bench")]
+pub mod benchmarks {
+    use super::{parse, INPUT};
+
+    #[divan::bench()]
+    fn bench_solve() {
+        for machine in parse(INPUT).unwrap() {
+            machine.with_offset(10000000000000).solve().unwrap();
+        }
+    }
+}
@@ -0,0 +1,464 @@
+use std::fmt;
+
+use glam::I64Vec2;
+
+const INPUT: &str = include_str!("input.txt");
+
+const BUTTON_PREFIX: usize = "Button _: X+".len();
+const PRIZE_PREFIX: usize = "Prize: X=".len();
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Overflow;
+
+impl fmt::Display for Overflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "overflows an i128")
+    }
+}
+
+impl std::error::Error for Overflow {}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ClawMachine {
+    pub a: I64Vec2,
+    pub b: I64Vec2,
+    pub prize: I64Vec2,
+}
+
+pub fn parse(input: &str) -> Vec<ClawMachine> {
+    input
+        .trim()
+        .split("\n\n")
+        .map(|chunk| {
+            let mut lines = chunk.lines();
+            let mut vec2 = |prefix: usize, separator: &str| {
+                let line = lines.next().expect("machine should have three lines");
+                let (x, y) = line[prefix..]
+                    .split_once(separator)
+                    .expect("line should have an x and a y");
+                I64Vec2::new(int(x), int(y))
+            };
+            ClawMachine {
+                a: vec2(BUTTON_PREFIX, ", Y+"),
+                b: vec2(BUTTON_PREFIX, ", Y+"),
+                prize: vec2(PRIZE_PREFIX, ", Y="),
+            }
+        })
+        .collect()
+}
+
+fn int(s: &str) -> i64 {
+    s.parse::<i64>().unwrap()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Presses {
+    pub a: i128,
+    pub b: i128,
+    pub tokens: i128,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Solver {
+    // tokens for each press of A and B
+    costs: (i128, i128),
+    // the most times either button can be pressed
+    limit: Option<i128>,
+    // added to both coordinates of every prize
+    offset: i128,
+}
+
+impl Default for Solver {
+    fn default() -> Self {
+        Self {
+            costs: (3, 1),
+            limit: None,
+            offset: 0,
+        }
+    }
+}
+
+impl Solver {
+    // costs have to be positive, or pressing a button could go on forever
+    pub fn with_costs(mut self, a: u32, b: u32) -> Self {
+        assert!(a > 0 && b > 0, "pressing a button should cost something");
+        self.costs = (a as i128, b as i128);
+        self
+    }
+
+    pub fn with_limit(mut self, presses: u32) -> Self {
+        self.limit = Some(presses as i128);
+        self
+    }
+
+    pub fn with_offset(mut self, offset: i64) -> Self {
+        self.offset = offset as i128;
+        self
+    }
+
+    // the cheapest presses that win the prize, `None` when it can't be won
+    pub fn solve(&self, machine: &ClawMachine) -> Result<Option<Presses>, Overflow> {
+        let a = (machine.a.x as i128, machine.a.y as i128);
+        let b = (machine.b.x as i128, machine.b.y as i128);
+        let p = (
+            add(machine.prize.x as i128, self.offset)?,
+            add(machine.prize.y as i128, self.offset)?,
+        );
+
+        // Cramer's rule on
+        // { A * a.x + B * b.x = p.x
+        // { A * a.y + B * b.y = p.y
+        let determinant = cross(a, b)?;
+        let presses = if determinant != 0 {
+            let da = cross(p, b)?;
+            let db = cross(a, p)?;
+            // the presses are whole numbers or there's no way to win
+            if da % determinant != 0 || db % determinant != 0 {
+                return Ok(None);
+            }
+            Some((da / determinant, db / determinant))
+                .filter(|&(a, b)| self.in_range(a) && self.in_range(b))
+        } else {
+            self.solve_collinear(a, b, p)?
+        };
+
+        presses
+            .map(|(a_presses, b_presses)| {
+                Ok(Presses {
+                    a: a_presses,
+                    b: b_presses,
+                    tokens: add(mul(a_presses, self.costs.0)?, mul(b_presses, self.costs.1)?)?,
+                })
+            })
+            .transpose()
+    }
+
+    fn in_range(&self, presses: i128) -> bool {
+        presses >= 0 && self.limit.is_none_or(|limit| presses <= limit)
+    }
+
+    // both buttons move the claw along the same line, so the prize has to be
+    // on it too, and then there's a whole family of presses that reach it
+    fn solve_collinear(
+        &self,
+        a: (i128, i128),
+        b: (i128, i128),
+        p: (i128, i128),
+    ) -> Result<Option<(i128, i128)>, Overflow> {
+        if cross(a, p)? != 0 || cross(b, p)? != 0 {
+            return Ok(None);
+        }
+        let Some(line) = [a, b].into_iter().find(|&v| v != (0, 0)) else {
+            // neither button moves the claw
+            return Ok((p == (0, 0)).then_some((0, 0)));
+        };
+
+        // every vector on the line is a whole number of steps along it
+        let g = gcd(line.0, line.1);
+        let step = (line.0 / g, line.1 / g);
+        let steps = |v: (i128, i128)| match step.0 {
+            0 => v.1 / step.1,
+            _ => v.0 / step.0,
+        };
+        let (alpha, beta, target) = (steps(a), steps(b), steps(p));
+
+        // alpha * A + beta * B = target has solutions when the gcd divides
+        // the target, A = A0 + k * beta / g and B = B0 - k * alpha / g
+        let (g, x, y) = extended_gcd(alpha, beta);
+        if target % g != 0 {
+            return Ok(None);
+        }
+        let m = target / g;
+        let (a0, b0) = (mul(x, m)?, mul(y, m)?);
+        let (a_step, b_step) = (beta / g, -alpha / g);
+
+        // the k that keep 0 <= start + k * step <= limit for both buttons,
+        // unbounded on a side where neither constrains it
+        let mut low: Option<i128> = None;
+        let mut high: Option<i128> = None;
+        for (start, step) in [(a0, a_step), (b0, b_step)] {
+            if step == 0 {
+                if !self.in_range(start) {
+                    return Ok(None);
+                }
+                continue;
+            }
+            let mut bounds = vec![(sub(0, start)?, true)];
+            if let Some(limit) = self.limit {
+                bounds.push((sub(limit, start)?, false));
+            }
+            // k * step >= bound when `at_least`, otherwise <=
+            for (bound, at_least) in bounds {
+                if at_least == (step > 0) {
+                    let from = div_ceil(bound, step);
+                    low = Some(low.map_or(from, |low| low.max(from)));
+                } else {
+                    let to = div_floor(bound, step);
+                    high = Some(high.map_or(to, |high| high.min(to)));
+                }
+            }
+        }
+
+        // the cost is linear in k, so the cheapest presses are at one end.
+        // Both ends are there when it matters, as A and B can't both grow
+        // forever while the cost goes down.
+        let slope = add(mul(self.costs.0, a_step)?, mul(self.costs.1, b_step)?)?;
+        let k = match (low, high) {
+            (Some(low), Some(high)) if low > high => return Ok(None),
+            (Some(low), _) if slope >= 0 => low,
+            (_, Some(high)) if slope <= 0 => high,
+            _ => unreachable!("positive costs keep the cheapest presses bounded"),
+        };
+        Ok(Some((add(a0, mul(k, a_step)?)?, add(b0, mul(k, b_step)?)?)))
+    }
+}
+
+fn add(a: i128, b: i128) -> Result<i128, Overflow> {
+    a.checked_add(b).ok_or(Overflow)
+}
+
+fn sub(a: i128, b: i128) -> Result<i128, Overflow> {
+    a.checked_sub(b).ok_or(Overflow)
+}
+
+fn mul(a: i128, b: i128) -> Result<i128, Overflow> {
+    a.checked_mul(b).ok_or(Overflow)
+}
+
+fn cross(u: (i128, i128), v: (i128, i128)) -> Result<i128, Overflow> {
+    sub(mul(u.0, v.1)?, mul(u.1, v.0)?)
+}
+
+fn div_floor(n: i128, d: i128) -> i128 {
+    let q = n / d;
+    match n % d != 0 && (n < 0) != (d < 0) {
+        true => q - 1,
+        false => q,
+    }
+}
+
+fn div_ceil(n: i128, d: i128) -> i128 {
+    let q = n / d;
+    match n % d != 0 && (n < 0) == (d < 0) {
+        true => q + 1,
+        false => q,
+    }
+}
+
+fn gcd(a: i128, b: i128) -> i128 {
+    extended_gcd(a, b).0
+}
+
+// (g, x, y) with a * x + b * y = g, and g never negative
+fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    let (mut r0, mut r1) = (a, b);
+    let (mut x0, mut x1) = (1, 0);
+    let (mut y0, mut y1) = (0, 1);
+    while r1 != 0 {
+        let q = r0 / r1;
+        (r0, r1) = (r1, r0 - q * r1);
+        (x0, x1) = (x1, x0 - q * x1);
+        (y0, y1) = (y1, y0 - q * y1);
+    }
+    match r0 < 0 {
+        true => (-r0, -x0, -y0),
+        false => (r0, x0, y0),
+    }
+}
+
+#[tracing::instrument(level = "trace", skip())]
+pub fn run(offset: i64) -> String {
+    process(INPUT, offset)
+}
+
+#[tracing::instrument(level = "trace", skip(input))]
+fn process(input: &str, offset: i64) -> String {
+    let solver = Solver::default().with_offset(offset);
+
+    let mut report = String::new();
+    let mut total = Ok(0);
+    for (i, machine) in parse(input).iter().enumerate() {
+        let line = match solver.solve(machine) {
+            Ok(Some(Presses { a, b, tokens })) => {
+                total = total.and_then(|total| add(total, tokens));
+                format!("{a} A, {b} B for {tokens} tokens")
+            }
+            Ok(None) => "no prize".to_string(),
+            Err(error) => {
+                total = Err(error);
+                error.to_string()
+            }
+        };
+        report.push_str(&format!("machine {}: {line}\n", i + 1));
+    }
+    match total {
+        Ok(total) => report.push_str(&format!("total: {total} tokens\n")),
+        Err(_) => report.push_str("total: unknown\n"),
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "Button A: X+94, Y+34
+Button B: X+22, Y+67
+Prize: X=8400, Y=5400
+
+Button A: X+26, Y+66
+Button B: X+67, Y+21
+Prize: X=12748, Y=12176
+
+Button A: X+17, Y+86
+Button B: X+84, Y+37
+Prize: X=7870, Y=6450
+
+Button A: X+69, Y+23
+Button B: X+27, Y+71
+Prize: X=18641, Y=10279";
+
+    fn machine(a: (i64, i64), b: (i64, i64), prize: (i64, i64)) -> ClawMachine {
+        ClawMachine {
+            a: I64Vec2::new(a.0, a.1),
+            b: I64Vec2::new(b.0, b.1),
+            prize: I64Vec2::new(prize.0, prize.1),
+        }
+    }
+
+    #[test]
+    fn it_works() {
+        assert_eq!(
+            process(EXAMPLE, 0),
+            "machine 1: 80 A, 40 B for 280 tokens
+machine 2: no prize
+machine 3: 38 A, 86 B for 200 tokens
+machine 4: no prize
+total: 480 tokens
+"
+        );
+        assert_eq!(
+            process(EXAMPLE, 10000000000000),
+            "machine 1: no prize
+machine 2: 118679050709 A, 103199174542 B for 459236326669 tokens
+machine 3: no prize
+machine 4: 102851800151 A, 107526881786 B for 416082282239 tokens
+total: 875318608908 tokens
+"
+        );
+    }
+
+    #[test]
+    fn test_collinear() {
+        let solver = Solver::default();
+        let presses = |a, b, tokens| Ok(Some(Presses { a, b, tokens }));
+        // B is cheaper per step, A is needed to make up the odd one
+        assert_eq!(
+            solver.solve(&machine((3, 6), (2, 4), (13, 26))),
+            presses(1, 5, 8)
+        );
+        // per step A is cheaper here, 3 tokens for 4 against 1 for 1
+        assert_eq!(
+            solver.solve(&machine((4, 4), (1, 1), (10, 10))),
+            presses(2, 2, 8)
+        );
+        // off the line, or not a whole number of steps along it
+        assert_eq!(solver.solve(&machine((2, 2), (4, 4), (3, 4))), Ok(None));
+        assert_eq!(solver.solve(&machine((2, 2), (4, 4), (3, 3))), Ok(None));
+        // the limit rules out the cheapest presses
+        let machine = machine((3, 0), (2, 0), (12, 0));
+        assert_eq!(solver.solve(&machine), presses(0, 6, 6));
+        assert_eq!(solver.with_limit(5).solve(&machine), presses(2, 3, 9));
+        assert_eq!(solver.with_limit(2).solve(&machine), Ok(None));
+    }
+
+    #[test]
+    fn test_stuck_buttons() {
+        let solver = Solver::default();
+        assert_eq!(
+            solver.solve(&machine((0, 0), (0, 0), (0, 0))),
+            Ok(Some(Presses {
+                a: 0,
+                b: 0,
+                tokens: 0
+            }))
+        );
+        assert_eq!(solver.solve(&machine((0, 0), (0, 0), (1, 0))), Ok(None));
+        assert_eq!(
+            solver.solve(&machine((0, 0), (0, 5), (0, 15))),
+            Ok(Some(Presses {
+                a: 0,
+                b: 3,
+                tokens: 3
+            }))
+        );
+    }
+
+    #[test]
+    fn test_overflow() {
+        let input = "Button A: X+1, Y+0
+Button B: X+9223372036854775807, Y+-9223372036854775808
+Prize: X=9223372036854775807, Y=9223372036854775807";
+        let solver = Solver::default().with_offset(i64::MAX);
+        assert_eq!(solver.solve(&parse(input)[0]), Err(Overflow));
+        assert_eq!(
+            process(input, i64::MAX),
+            "machine 1: overflows an i128\ntotal: unknown\n"
+        );
+    }
+
+    // the cheapest presses by trying every number of A presses
+    fn search(solver: &Solver, machine: &ClawMachine) -> Option<i128> {
+        let (a, b, p) = (machine.a, machine.b, machine.prize);
+        (0..=100)
+            .filter_map(|a_presses| {
+                let rest = p - a * a_presses;
+                (0..=100)
+                    .find(|&b_presses| b * b_presses == rest)
+                    .map(|b_presses| a_presses as i128 * solver.costs.0 + b_presses as i128)
+            })
+            .min()
+    }
+
+    #[test]
+    fn test_matches_search() {
+        let solver = Solver::default().with_limit(100);
+        let mut state: u64 = 7;
+        let mut random = |n: u64| {
+            // xorshift64
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state % n) as i64
+        };
+        for _ in 0..300 {
+            let a = I64Vec2::new(random(6), random(6));
+            // collinear buttons every other time
+            let b = match random(2) {
+                0 => a * (1 + random(3)),
+                _ => I64Vec2::new(random(6), random(6)),
+            };
+            let prize = a * random(20) + b * random(20) + I64Vec2::splat(random(2));
+            let machine = ClawMachine { a, b, prize };
+
+            let tokens = solver.solve(&machine).unwrap().map(|presses| {
+                assert_eq!(a * presses.a as i64 + b * presses.b as i64, prize);
+                presses.tokens
+            });
+            assert_eq!(tokens, search(&solver, &machine), "{machine:?}");
+        }
+    }
+}
+
+#[cfg(feature = "bench")]
+pub mod benchmarks {
+    use super::{parse, Solver, INPUT};
+
+    #[divan::bench()]
+    fn bench_solve() {
+        let solver = Solver::default().with_offset(10000000000000);
+        for machine in parse(INPUT) {
+            solver.solve(&machine).unwrap();
+        }
+    }
+}
@@ -1,2 +1,4 @@
+pub mod buttons;
+pub mod claw;
 pub mod part1;
 pub mod part2;
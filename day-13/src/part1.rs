@@ -1,175 +1,22 @@
-use std::cmp::Ordering;
-
-use glam::IVec2;
+use crate::claw::{parse, Overflow, Solver};
 
 const INPUT: &str = include_str!("input.txt");
 
 #[tracing::instrument(level = "trace", skip())]
 pub fn run() -> String {
-    process(INPUT).to_string()
-}
-
-#[derive(Debug, Default)]
-struct ClawMachine {
-    a: IVec2,
-    b: IVec2,
-    p: IVec2,
+    match process(INPUT) {
+        Ok(tokens) => tokens.to_string(),
+        Err(error) => error.to_string(),
+    }
 }
 
-const BUTTON_PREFIX: usize = "Button _: X+".len();
-const PRIZE_PREFIX: usize = "Prize: X=".len();
-
 #[tracing::instrument(level = "trace", skip(input))]
-fn process(input: &str) -> usize {
-    let tokens = input
-        .trim()
-        .split("\n\n")
-        .map(|chunk| {
-            chunk
-                .lines()
-                .enumerate()
-                .filter_map(|(i, line)| -> Option<(usize, IVec2)> {
-                    match i {
-                        0 | 1 => line[BUTTON_PREFIX..].split_once(", Y+"),
-                        2 => line[PRIZE_PREFIX..].split_once(", Y="),
-                        _ => None,
-                    }
-                    .map(|(x, y)| (i, IVec2::new(int(x), int(y))))
-                })
-                .fold(ClawMachine::default(), |mut claw, (i, point)| {
-                    match i {
-                        0 => claw.a = point,
-                        1 => claw.b = point,
-                        2 => claw.p = point,
-                        _ => {}
-                    }
-                    claw
-                })
-        })
-        .filter(|ClawMachine { a, b, p }| {
-            // Remove items that can never go far enough
-            (a.x + b.x) * 100 > p.x && (a.y + b.y) * 100 > p.y
-        })
-        .map(|ClawMachine { a, b, p }| {
-            let mut claw = a;
-
-            let slope = |IVec2 { x, y }| x as f32 / y as f32;
-            let p_slope = slope(p);
-            let a_slope = slope(a);
-            let b_slope = slope(b);
-            assert!(a_slope != b_slope);
-
-            let mut a_count = 1;
-            let mut b_count = 0;
-            let mut count = 3;
-
-            #[cfg(debug_assertions)]
-            println!();
-            #[cfg(debug_assertions)]
-            println!();
-            #[cfg(debug_assertions)]
-            println!();
-
-            #[cfg(debug_assertions)]
-            println!("prize: {:?}, a: {:?}, b: {:?}", p, a, b);
-
-            #[cfg(debug_assertions)]
-            print!("🌕");
-            while claw != p && b_count < 100 && a_count < 100 {
-                let c_slope = slope(claw);
-
-                match c_slope.partial_cmp(&p_slope) {
-                    Some(Ordering::Equal) => {
-                        #[cfg(debug_assertions)]
-                        print!("");
-                        // vector is the same - so be able to multiply the current count by N to get to the point
-                        if claw == p {
-                            #[cfg(debug_assertions)]
-                            print!("🟢");
-
-                            return count;
-                        } else {
-                            let n = p.x / claw.x;
-                            let m = p.y / claw.y;
-
-                            if n == m && n * claw.x == p.x && m * claw.y == p.y {
-                                #[cfg(debug_assertions)]
-                                print!("🟣");
-                                return count * n as usize;
-                            } else {
-                                return 0;
-                                // #[cfg(debug_assertions)]
-                                // print!("🔵");
-                                // // the slope is the same - but there isn't a common factor
-                                // #[cfg(debug_assertions)]
-                                // println!(
-                                //     "equal slope prize: {:?}, claw: {:?}, a: {:?}, b: {:?}, {:?} {:?}",
-                                //     p, claw, a, b, c_slope, p_slope
-                                // );
-
-                                // #[cfg(debug_assertions)]
-                                // print!("🔴");
-                                // b_count +=1;
-                                // claw += b;
-                                // count += 1;
-                            }
-                        }
-                    }
-                    Some(Ordering::Greater) => {
-                        if a_slope > b_slope {
-                            #[cfg(debug_assertions)]
-                            print!("🔴");
-                            claw += b;
-                            count += 1;
-                            b_count += 1;
-                        } else {
-                            #[cfg(debug_assertions)]
-                            print!("🌕");
-                            claw += a;
-                            count += 3;
-                            a_count += 1;
-                        }
-                    }
-                    Some(Ordering::Less) => {
-                        if a_slope > b_slope {
-                            #[cfg(debug_assertions)]
-                            print!("🌕");
-                            claw += a;
-                            count += 3;
-                            a_count += 1;
-                        } else {
-                            #[cfg(debug_assertions)]
-                            print!("🔴");
-                            claw += b;
-                            count += 1;
-                            b_count += 1;
-                        }
-                    }
-                    None => todo!(),
-                }
-
-                if claw.x > p.x || claw.y > p.y {
-                    break;
-                }
-            }
-
-            if claw == p {
-                count
-            } else {
-                0
-            }
-        })
-        .sum();
-    #[cfg(debug_assertions)]
-    println!();
-    #[cfg(debug_assertions)]
-    println!();
-
-    tokens
-}
-
-fn int(s: &str) -> i32 {
-    s.parse::<i32>().unwrap()
+fn process(input: &str) -> Result<i128, Overflow> {
+    let solver = Solver::default().with_limit(100);
+    parse(input).iter().try_fold(0i128, |total, machine| {
+        let tokens = solver.solve(machine)?.map_or(0, |presses| presses.tokens);
+        total.checked_add(tokens).ok_or(Overflow)
+    })
 }
 
 #[cfg(test)]
@@ -195,7 +42,7 @@ Button A: X+69, Y+23
 Button B: X+27, Y+71
 Prize: X=18641, Y=10279",
         );
-        assert_eq!(result, 480);
+        assert_eq!(result, Ok(480));
     }
 }
 
@@ -209,6 +56,6 @@ pub mod benchmarks {
 
     #[divan::bench()]
     fn bench_process() {
-        super::process(INPUT);
+        super::process(INPUT).unwrap();
     }
 }
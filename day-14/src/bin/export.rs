@@ -0,0 +1,62 @@
+// renders the robots without a window, to numbered PNGs in a directory or to
+// one animated PNG, like
+// `cargo run -p day-14 --bin export -- --tick 6300 --frames 100 --fps 10 --apng robots.png`
+// The tick, speed and direction work like the visualiser's settings, and
+// `--input PATH` and `--size WIDTHxHEIGHT` pick other robots and rooms.
+use std::path::PathBuf;
+
+use day_14::render::{export_apng, export_pngs, Configuration};
+use day_14::robot::Room;
+
+fn main() {
+    let mut config = Configuration::default();
+    let mut frames = 100;
+    let mut fps = 10;
+    let mut scale = 4;
+    let mut apng: Option<PathBuf> = None;
+    let mut dir = PathBuf::from("frames");
+
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let room = Room::from_args(&mut args).unwrap_or_else(|error| {
+        eprintln!("{error}");
+        std::process::exit(1);
+    });
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .unwrap_or_else(|| panic!("{arg} should have a value"))
+        };
+        match arg.as_str() {
+            "--tick" => config.tick = value().parse().expect("tick should be a number"),
+            "--speed" => config.speed = value().parse().expect("speed should be a number"),
+            "--reverse" => config.reverse = true,
+            "--jump" => config.smooth = false,
+            "--frames" => frames = value().parse().expect("frames should be a number"),
+            "--fps" => {
+                fps = value()
+                    .parse::<u16>()
+                    .ok()
+                    .filter(|&fps| fps > 0)
+                    .expect("fps should be a number from 1 to 65535")
+                    .into()
+            }
+            "--scale" => scale = value().parse().expect("scale should be a number"),
+            "--apng" => apng = Some(PathBuf::from(value())),
+            "--dir" => dir = PathBuf::from(value()),
+            _ => panic!("unknown argument {arg}"),
+        }
+    }
+
+    let written = match &apng {
+        Some(path) => export_apng(path, &room, &config, frames, fps, scale).map(|()| path.clone()),
+        None => export_pngs(&dir, &room, &config, frames, fps, scale).map(|_| dir.clone()),
+    };
+    match written {
+        Ok(path) => println!("wrote {frames} frames to {}", path.display()),
+        Err(error) => {
+            eprintln!("{error}");
+            std::process::exit(1);
+        }
+    }
+}
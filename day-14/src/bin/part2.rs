@@ -1,3 +1,6 @@
+// shows the robots moving around the room, like
+// `cargo run -p day-14 --bin part2 -- --input example.txt --size 11x7`
+// Without arguments it's the puzzle input in the puzzle's 101x103 room.
 use std::time::Duration;
 
 use bevy::prelude::*;
@@ -6,13 +9,13 @@ use bevy_inspector_egui::inspector_options::std_options::NumberDisplay;
 use bevy_inspector_egui::prelude::*;
 use bevy_inspector_egui::quick::ResourceInspectorPlugin;
 use bevy_inspector_egui::DefaultInspectorConfigPlugin;
+use day_14::render;
+use day_14::robot::{safety_factor, Robot, Room};
 
-const INPUT: &str = include_str!("../input.txt");
-const GRID_WIDTH: f32 = 101.0;
-const GRID_HEIGHT: f32 = 103.0;
 const SIZE: f32 = 8.0;
-const WINDOW_WIDTH: f32 = GRID_WIDTH * SIZE;
-const WINDOW_HEIGHT: f32 = GRID_HEIGHT * SIZE;
+
+#[derive(Resource)]
+struct Bathroom(Room);
 
 #[derive(Reflect, Resource, InspectorOptions)]
 #[reflect(Resource, InspectorOptions)]
@@ -24,25 +27,62 @@ struct Configuration {
     paused: bool,
     reverse: bool,
     smooth: bool,
+    // part 1's robots per quadrant at the tick on screen, and their product
+    quadrants: [usize; 4],
+    safety_factor: usize,
 }
 
+// the settings are the same as the headless export's, so a recording can
+// start from what's on screen
 impl Default for Configuration {
     fn default() -> Self {
+        let render::Configuration {
+            tick,
+            speed,
+            paused,
+            reverse,
+            smooth,
+        } = render::Configuration::default();
         Self {
-            tick: 6370.0,
-            speed: 1.0,
-            paused: true,
-            reverse: false,
-            smooth: true,
+            tick,
+            speed,
+            paused,
+            reverse,
+            smooth,
+            quadrants: [0; 4],
+            safety_factor: 0,
+        }
+    }
+}
+
+impl From<&Configuration> for render::Configuration {
+    fn from(config: &Configuration) -> Self {
+        Self {
+            tick: config.tick,
+            speed: config.speed,
+            paused: config.paused,
+            reverse: config.reverse,
+            smooth: config.smooth,
         }
     }
 }
 
 fn main() {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let room = Room::from_args(&mut args).unwrap_or_else(|error| {
+        eprintln!("{error}");
+        std::process::exit(1);
+    });
+    if let Some(arg) = args.first() {
+        eprintln!("unknown argument {arg}");
+        std::process::exit(1);
+    }
+    let window = room.grid.as_vec2() * SIZE;
+
     App::new()
         .add_plugins(DefaultPlugins.set(WindowPlugin {
             primary_window: Some(Window {
-                resolution: [WINDOW_WIDTH, WINDOW_HEIGHT].into(),
+                resolution: [window.x, window.y].into(),
                 title: "Advent of code 2024 - day 14 - part 2".to_string(),
                 ..default()
             }),
@@ -53,30 +93,29 @@ fn main() {
         .add_plugins(DefaultInspectorConfigPlugin)
         // insert and register resource
         .init_resource::<Configuration>()
+        .insert_resource(Bathroom(room))
         .insert_resource(Time::<Virtual>::from_max_delta(Duration::from_secs(5)))
         .register_type::<Configuration>()
         .add_systems(Startup, (spawn_camera, spawn_robots))
         .add_systems(Update, sync_robot_transforms)
         .add_systems(Update, auto_tick)
         .add_systems(Update, update_speed)
+        .add_systems(Update, update_quadrants)
         .add_plugins(ResourceInspectorPlugin::<Configuration>::default())
         .run();
 }
 
 fn sync_robot_transforms(
-    mut query: Query<(&mut Robot, &mut Transform)>,
+    mut query: Query<(&RobotSprite, &mut Transform)>,
     config: Res<Configuration>,
+    bathroom: Res<Bathroom>,
 ) {
     let scaling = Vec3::new(SIZE, SIZE, 1.0);
-    let tick = if config.smooth {
-        config.tick
-    } else {
-        config.tick.trunc()
-    };
+    let tick = render::Configuration::from(&*config).shown_tick(config.tick);
 
-    for (mut robot, mut transform) in &mut query {
-        let position = robot.simulation_at(tick);
-        transform.translation = position * scaling;
+    for (RobotSprite(robot), mut transform) in &mut query {
+        let position = robot.position_at_f32(tick, bathroom.0.grid);
+        transform.translation = Vec3::new(position.x, position.y, 0.0) * scaling;
     }
 }
 
@@ -91,8 +130,18 @@ fn auto_tick(mut config: ResMut<Configuration>, time: Res<Time<Virtual>>) {
     }
 }
 
-fn spawn_camera(mut commands: Commands) {
-    let center = Vec3::new(WINDOW_WIDTH * 0.5, WINDOW_HEIGHT * 0.5, 0.0);
+// the counts are for whole ticks, so they only change as a robot lands
+fn update_quadrants(mut config: ResMut<Configuration>, bathroom: Res<Bathroom>) {
+    let tick = render::Configuration::from(&*config).shown_tick(config.tick);
+    let quadrants = bathroom.0.quadrants_at(tick.floor() as i64);
+    if config.quadrants != quadrants {
+        config.quadrants = quadrants;
+        config.safety_factor = safety_factor(quadrants);
+    }
+}
+
+fn spawn_camera(mut commands: Commands, bathroom: Res<Bathroom>) {
+    let center = (bathroom.0.grid.as_vec2() * SIZE * 0.5).extend(0.0);
     commands.spawn((
         Camera2d,
         Transform::from_translation(center).looking_at(center, Vec3::NEG_Y),
@@ -103,47 +152,26 @@ fn spawn_robots(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
+    bathroom: Res<Bathroom>,
 ) {
     let square = meshes.add(Rectangle::new(SIZE, SIZE));
 
-    for (i, robot) in get_robots().enumerate() {
+    for (i, &robot) in bathroom.0.robots.iter().enumerate() {
         commands.spawn((
             Mesh2d(square.clone()),
             MeshMaterial2d(materials.add(Color::hsl(3.6 * i as f32, 0.75, 0.75))),
-            Transform::from_xyz(robot.position.x * SIZE, robot.position.y * SIZE, 0.0),
-            robot,
+            Transform::from_xyz(
+                robot.position.x as f32 * SIZE,
+                robot.position.y as f32 * SIZE,
+                0.0,
+            ),
+            RobotSprite(robot),
         ));
     }
 }
 
 #[derive(Debug, Component)]
-struct Robot {
-    position: Vec3,
-    velocity: Vec3,
-}
-
-impl Robot {
-    fn simulation_at(&mut self, tick: f32) -> Vec3 {
-        (self.position + self.velocity * tick).rem_euclid(Vec3::new(GRID_WIDTH, GRID_HEIGHT, 1.0))
-    }
-}
-
-fn get_robots() -> impl Iterator<Item = Robot> {
-    INPUT.lines().map(|line| {
-        let line = line.strip_prefix("p=").unwrap();
-        let (position, velocity) = line.split_once(" v=").unwrap();
-        let (x, y) = position.split_once(',').unwrap();
-        let x = x.parse::<f32>().unwrap();
-        let y = y.parse::<f32>().unwrap();
-        let position = Vec3::new(x, y, 0.0);
-        let (x, y) = velocity.split_once(',').unwrap();
-        let x = x.parse::<f32>().unwrap();
-        let y = y.parse::<f32>().unwrap();
-        let velocity = Vec3::new(x, y, 0.0);
-        Robot { position, velocity }
-    })
-    // .take(1)
-}
+struct RobotSprite(Robot);
 
 fn update_speed(mut time: ResMut<Time<Virtual>>, config: Res<Configuration>) {
     time.set_relative_speed(config.speed.max(0.25));
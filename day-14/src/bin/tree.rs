@@ -0,0 +1,5 @@
+// prints the tick that the robots draw the tree at, found by each way of
+// scoring how ordered they are, to check in the visualiser
+fn main() {
+    print!("{}", day_14::tree::run());
+}
@@ -1 +1,5 @@
 pub mod part1;
+pub mod part2;
+pub mod render;
+pub mod robot;
+pub mod tree;
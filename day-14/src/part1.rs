@@ -1,90 +1,27 @@
 use glam::IVec2;
 
+use crate::robot::{parse, quadrants, safety_factor, GRID};
+
 const INPUT: &str = include_str!("input.txt");
 
 #[tracing::instrument(level = "trace", skip())]
 pub fn run() -> String {
-    let grid = IVec2::new(101, 103);
     let ticks = 100;
-    process(INPUT, grid, ticks).to_string()
-}
-
-struct Robot {
-    position: IVec2,
-    velocity: IVec2,
-}
-
-impl Robot {
-    fn simulate(&mut self, grid: IVec2) {
-        self.position += self.velocity;
-        if self.position.x < 0 {
-            self.position.x += grid.x;
-        }
-        if self.position.y < 0 {
-            self.position.y += grid.y;
-        }
-        if self.position.x >= grid.x {
-            self.position.x -= grid.x;
-        }
-        if self.position.y >= grid.y {
-            self.position.y -= grid.y;
-        }
-    }
-
-    fn quadrant(&self, grid: IVec2) -> usize {
-        let mid_x = grid.x / 2;
-        let mid_y = grid.y / 2;
-
-        match (self.position.x, self.position.y) {
-            (x, y) if x < mid_x && y < mid_y => 1,
-            (x, y) if x > mid_x && y < mid_y => 2,
-            (x, y) if x < mid_x && y > mid_y => 3,
-            (x, y) if x > mid_x && y > mid_y => 4,
-            _ => 0,
-        }
-    }
+    process(INPUT, GRID, ticks).to_string()
 }
 
 #[tracing::instrument(level = "trace", skip(input))]
 fn process(input: &str, grid: IVec2, ticks: usize) -> usize {
-    let quadrants = input
-        .lines()
-        .map(|line| {
-            let line = line.strip_prefix("p=").unwrap();
-            let (position, velocity) = line.split_once(" v=").unwrap();
-            let (x, y) = position.split_once(',').unwrap();
-            let x = x.parse::<i32>().unwrap();
-            let y = y.parse::<i32>().unwrap();
-            let position = IVec2::new(x, y);
-            let (x, y) = velocity.split_once(',').unwrap();
-            let x = x.parse::<i32>().unwrap();
-            let y = y.parse::<i32>().unwrap();
-            let velocity = IVec2::new(x, y);
-            let mut robot = Robot { position, velocity };
-
-            for _ in 0..ticks {
-                robot.simulate(grid);
-            }
-
-            robot.quadrant(grid)
-        })
-        .fold((0, 0, 0, 0), |mut quadrants, quadrant| {
-            match quadrant {
-                1 => quadrants.0 += 1,
-                2 => quadrants.1 += 1,
-                3 => quadrants.2 += 1,
-                4 => quadrants.3 += 1,
-                _ => {}
-            };
-            quadrants
-        });
-
-    quadrants.0 * quadrants.1 * quadrants.2 * quadrants.3
+    let positions = parse(input)
+        .into_iter()
+        .map(|robot| robot.position_at(ticks as i64, grid));
+    safety_factor(quadrants(positions, grid))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::robot::Robot;
 
     #[test]
     fn test_robot_simulation() {
@@ -156,9 +93,7 @@ pub mod benchmarks {
 
     #[divan::bench()]
     fn bench_process() {
-        use glam::IVec2;
-        let grid = IVec2::new(101, 103);
         let ticks = 100;
-        super::process(INPUT, grid, ticks);
+        super::process(INPUT, crate::robot::GRID, ticks);
     }
 }
@@ -0,0 +1,43 @@
+use glam::IVec2;
+
+use crate::robot::{parse, GRID};
+use crate::tree::{find_tree, Score};
+
+const INPUT: &str = include_str!("input.txt");
+
+#[tracing::instrument(level = "trace", skip())]
+pub fn run() -> String {
+    process(INPUT, GRID).to_string()
+}
+
+#[tracing::instrument(level = "trace", skip(input))]
+fn process(input: &str, grid: IVec2) -> i64 {
+    find_tree(&parse(input), grid, Score::Variance).expect("there should be robots")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree::tests::robots_drawing_at;
+
+    #[test]
+    fn it_works() {
+        let grid = IVec2::new(31, 37);
+        let result = process(&robots_drawing_at(1000, grid), grid);
+        assert_eq!(result, 1000);
+    }
+}
+
+#[cfg(feature = "bench")]
+pub mod benchmarks {
+    use super::INPUT;
+
+    pub fn main() {
+        divan::main();
+    }
+
+    #[divan::bench()]
+    fn bench_process() {
+        super::process(INPUT, crate::robot::GRID);
+    }
+}
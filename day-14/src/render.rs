@@ -0,0 +1,404 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use glam::IVec2;
+
+use crate::robot::{Robot, Room};
+
+// the visualiser's background, bevy's default clear colour
+const BACKGROUND: [u8; 3] = [43, 44, 47];
+
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+// what the visualiser is showing, so a recording can start where it's at
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Configuration {
+    pub tick: f32,
+    // ticks a second
+    pub speed: f32,
+    pub paused: bool,
+    pub reverse: bool,
+    // in between ticks too, rather than jumping from one to the next
+    pub smooth: bool,
+}
+
+impl Default for Configuration {
+    fn default() -> Self {
+        Self {
+            tick: 6370.0,
+            speed: 1.0,
+            paused: true,
+            reverse: false,
+            smooth: true,
+        }
+    }
+}
+
+impl Configuration {
+    // the tick `seconds` of playing after this one
+    pub fn tick_after(&self, seconds: f32) -> f32 {
+        let ticks = seconds * self.speed.max(0.25);
+        match self.reverse {
+            true => self.tick - ticks,
+            false => self.tick + ticks,
+        }
+    }
+
+    // the tick the robots are drawn at
+    pub fn shown_tick(&self, tick: f32) -> f32 {
+        match self.smooth {
+            true => tick,
+            false => tick.trunc(),
+        }
+    }
+
+    // the ticks of `frames` frames played at `fps` from this one
+    pub fn frame_ticks(&self, frames: usize, fps: u32) -> impl Iterator<Item = f32> + '_ {
+        (0..frames).map(move |frame| self.shown_tick(self.tick_after(frame as f32 / fps as f32)))
+    }
+}
+
+// an RGB image
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<[u8; 3]>,
+}
+
+impl Frame {
+    fn fill(&mut self, x: i32, y: i32, size: i32, colour: [u8; 3]) {
+        for y in y.max(0)..(y + size).min(self.height as i32) {
+            for x in x.max(0)..(x + size).min(self.width as i32) {
+                self.pixels[y as usize * self.width + x as usize] = colour;
+            }
+        }
+    }
+
+    // the rows as PNG wants them, each after a byte for no filter
+    fn scanlines(&self) -> Vec<u8> {
+        let mut raw = Vec::with_capacity(self.height * (1 + self.width * 3));
+        for row in self.pixels.chunks(self.width) {
+            raw.push(0);
+            raw.extend(row.iter().flatten());
+        }
+        raw
+    }
+}
+
+// draws each robot as a `scale` pixel square over its tile, in the same
+// colours as the visualiser
+pub fn render(robots: &[Robot], tick: f32, grid: IVec2, scale: u32) -> Frame {
+    let scale = scale as i32;
+    let mut frame = Frame {
+        width: (grid.x * scale) as usize,
+        height: (grid.y * scale) as usize,
+        pixels: vec![BACKGROUND; (grid.x * grid.y * scale * scale) as usize],
+    };
+    for (i, robot) in robots.iter().enumerate() {
+        let position = (robot.position_at_f32(tick, grid) * scale as f32)
+            .round()
+            .as_ivec2();
+        frame.fill(
+            position.x,
+            position.y,
+            scale,
+            hsl(3.6 * i as f32, 0.75, 0.75),
+        );
+    }
+    frame
+}
+
+// hue in degrees, saturation and lightness from 0 to 1
+fn hsl(hue: f32, saturation: f32, lightness: f32) -> [u8; 3] {
+    let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+    let hue = hue.rem_euclid(360.0) / 60.0;
+    let x = chroma * (1.0 - (hue % 2.0 - 1.0).abs());
+    let (r, g, b) = match hue as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let m = lightness - chroma / 2.0;
+    [r, g, b].map(|channel| ((channel + m) * 255.0).round() as u8)
+}
+
+fn chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend((data.len() as u32).to_be_bytes());
+    png.extend(kind);
+    png.extend(data);
+    let mut crc = crc32fast::Hasher::new();
+    crc.update(kind);
+    crc.update(data);
+    png.extend(crc.finalize().to_be_bytes());
+}
+
+fn header(png: &mut Vec<u8>, frame: &Frame) {
+    png.extend(PNG_SIGNATURE);
+    let mut ihdr = vec![];
+    ihdr.extend((frame.width as u32).to_be_bytes());
+    ihdr.extend((frame.height as u32).to_be_bytes());
+    // 8 bit RGB, deflate, no filtering, not interlaced
+    ihdr.extend([8, 2, 0, 0, 0]);
+    chunk(png, b"IHDR", &ihdr);
+}
+
+pub fn encode_png(frame: &Frame) -> Vec<u8> {
+    let mut png = vec![];
+    header(&mut png, frame);
+    let data = miniz_oxide::deflate::compress_to_vec_zlib(&frame.scanlines(), 6);
+    chunk(&mut png, b"IDAT", &data);
+    chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+// an animated PNG that loops forever. Viewers that don't know about APNG show
+// the first frame.
+pub fn encode_apng(frames: &[Frame], fps: u32) -> Vec<u8> {
+    let fps = u16::try_from(fps)
+        .ok()
+        .filter(|&fps| fps > 0)
+        .expect("fps should be from 1 to 65535");
+    let first = frames.first().expect("there should be a frame");
+    let mut png = vec![];
+    header(&mut png, first);
+
+    let mut actl = vec![];
+    actl.extend((frames.len() as u32).to_be_bytes());
+    actl.extend(0u32.to_be_bytes());
+    chunk(&mut png, b"acTL", &actl);
+
+    // fcTL and fdAT chunks share a sequence
+    let mut sequence = 0u32;
+    for (i, frame) in frames.iter().enumerate() {
+        assert!(
+            frame.width == first.width && frame.height == first.height,
+            "frames should all be the same size"
+        );
+        let mut fctl = vec![];
+        fctl.extend(sequence.to_be_bytes());
+        fctl.extend((frame.width as u32).to_be_bytes());
+        fctl.extend((frame.height as u32).to_be_bytes());
+        // at the top left
+        fctl.extend(0u32.to_be_bytes());
+        fctl.extend(0u32.to_be_bytes());
+        // shown for 1 / fps seconds
+        fctl.extend(1u16.to_be_bytes());
+        fctl.extend(fps.to_be_bytes());
+        // replacing everything that was there
+        fctl.extend([0, 0]);
+        chunk(&mut png, b"fcTL", &fctl);
+        sequence += 1;
+
+        let data = miniz_oxide::deflate::compress_to_vec_zlib(&frame.scanlines(), 6);
+        match i {
+            0 => chunk(&mut png, b"IDAT", &data),
+            _ => {
+                let mut fdat = sequence.to_be_bytes().to_vec();
+                fdat.extend(data);
+                chunk(&mut png, b"fdAT", &fdat);
+                sequence += 1;
+            }
+        }
+    }
+    chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+// an APNG frame's delay is a fraction of a second with a u16 denominator
+fn check_fps(fps: u32) -> io::Result<()> {
+    match fps {
+        1..=0xffff => Ok(()),
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("fps should be from 1 to 65535, not {fps}"),
+        )),
+    }
+}
+
+// writes `frame-00000.png` and on into `dir`, returning how many there are
+pub fn export_pngs(
+    dir: &Path,
+    room: &Room,
+    config: &Configuration,
+    frames: usize,
+    fps: u32,
+    scale: u32,
+) -> io::Result<usize> {
+    check_fps(fps)?;
+    fs::create_dir_all(dir)?;
+    for (i, tick) in config.frame_ticks(frames, fps).enumerate() {
+        let frame = render(&room.robots, tick, room.grid, scale);
+        fs::write(dir.join(format!("frame-{i:05}.png")), encode_png(&frame))?;
+    }
+    Ok(frames)
+}
+
+pub fn export_apng(
+    path: &Path,
+    room: &Room,
+    config: &Configuration,
+    frames: usize,
+    fps: u32,
+    scale: u32,
+) -> io::Result<()> {
+    check_fps(fps)?;
+    let frames: Vec<Frame> = config
+        .frame_ticks(frames, fps)
+        .map(|tick| render(&room.robots, tick, room.grid, scale))
+        .collect();
+    fs::write(path, encode_apng(&frames, fps))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::robot::parse;
+
+    // (kind, data) of each chunk, checking their CRCs
+    fn chunks(png: &[u8]) -> Vec<(String, Vec<u8>)> {
+        assert_eq!(png[..8], PNG_SIGNATURE);
+        let mut chunks = vec![];
+        let mut rest = &png[8..];
+        while !rest.is_empty() {
+            let len = u32::from_be_bytes(rest[..4].try_into().unwrap()) as usize;
+            let (kind, data) = (&rest[4..8], &rest[8..8 + len]);
+            let crc = u32::from_be_bytes(rest[8 + len..12 + len].try_into().unwrap());
+            assert_eq!(crc, crc32fast::hash(&rest[4..8 + len]));
+            chunks.push((String::from_utf8(kind.to_vec()).unwrap(), data.to_vec()));
+            rest = &rest[12 + len..];
+        }
+        chunks
+    }
+
+    #[test]
+    fn test_hsl() {
+        assert_eq!(hsl(0.0, 1.0, 0.5), [255, 0, 0]);
+        assert_eq!(hsl(120.0, 1.0, 0.5), [0, 255, 0]);
+        assert_eq!(hsl(240.0, 0.75, 0.75), [143, 143, 239]);
+        assert_eq!(hsl(360.0, 0.0, 1.0), [255, 255, 255]);
+    }
+
+    #[test]
+    fn test_render() {
+        let robots = parse("p=1,0 v=1,1\np=0,0 v=0,0");
+        let grid = IVec2::new(3, 2);
+        let frame = render(&robots, 0.5, grid, 2);
+        assert_eq!((frame.width, frame.height), (6, 4));
+
+        // the first robot is half way to (2, 1), the second one is still
+        let pixel = |x: usize, y: usize| frame.pixels[y * frame.width + x];
+        let first = hsl(0.0, 0.75, 0.75);
+        let second = hsl(3.6, 0.75, 0.75);
+        assert_eq!([pixel(3, 1), pixel(4, 2)], [first, first]);
+        assert_eq!([pixel(0, 0), pixel(1, 1)], [second, second]);
+        assert_eq!([pixel(2, 0), pixel(5, 0), pixel(0, 3)], [BACKGROUND; 3]);
+
+        // without smoothing it's still on its tile
+        let config = Configuration {
+            tick: 0.5,
+            smooth: false,
+            ..Default::default()
+        };
+        let tick = config.frame_ticks(1, 30).next().unwrap();
+        let frame = render(&robots, tick, grid, 2);
+        assert_eq!(frame.pixels[2], first);
+    }
+
+    #[test]
+    fn test_frame_ticks() {
+        let config = Configuration {
+            tick: 10.0,
+            speed: 2.0,
+            ..Default::default()
+        };
+        let ticks: Vec<f32> = config.frame_ticks(3, 4).collect();
+        assert_eq!(ticks, [10.0, 10.5, 11.0]);
+
+        let config = Configuration {
+            reverse: true,
+            smooth: false,
+            ..config
+        };
+        let ticks: Vec<f32> = config.frame_ticks(3, 4).collect();
+        assert_eq!(ticks, [10.0, 9.0, 9.0]);
+    }
+
+    #[test]
+    fn test_png() {
+        let robots = parse("p=1,0 v=1,1");
+        let frame = render(&robots, 0.0, IVec2::new(3, 2), 1);
+        let png = encode_png(&frame);
+
+        let chunks = chunks(&png);
+        let kinds: Vec<&str> = chunks.iter().map(|(kind, _)| kind.as_str()).collect();
+        assert_eq!(kinds, ["IHDR", "IDAT", "IEND"]);
+        assert_eq!(chunks[0].1, [0, 0, 0, 3, 0, 0, 0, 2, 8, 2, 0, 0, 0]);
+
+        let raw = miniz_oxide::inflate::decompress_to_vec_zlib(&chunks[1].1).unwrap();
+        assert_eq!(raw.len(), 2 * (1 + 3 * 3));
+        assert_eq!(raw[0], 0);
+        assert_eq!(raw[4..7], hsl(0.0, 0.75, 0.75));
+    }
+
+    #[test]
+    fn test_apng() {
+        let robots = parse("p=1,0 v=1,1");
+        let grid = IVec2::new(3, 2);
+        let frames: Vec<Frame> = (0..3)
+            .map(|tick| render(&robots, tick as f32, grid, 1))
+            .collect();
+        let png = encode_apng(&frames, 10);
+
+        let chunks = chunks(&png);
+        let kinds: Vec<&str> = chunks.iter().map(|(kind, _)| kind.as_str()).collect();
+        assert_eq!(
+            kinds,
+            ["IHDR", "acTL", "fcTL", "IDAT", "fcTL", "fdAT", "fcTL", "fdAT", "IEND"]
+        );
+        assert_eq!(chunks[1].1, [0, 0, 0, 3, 0, 0, 0, 0]);
+
+        let sequence = |data: &[u8]| u32::from_be_bytes(data[..4].try_into().unwrap());
+        let numbers: Vec<u32> = chunks
+            .iter()
+            .filter(|(kind, _)| kind == "fcTL" || kind == "fdAT")
+            .map(|(_, data)| sequence(data))
+            .collect();
+        assert_eq!(numbers, [0, 1, 2, 3, 4]);
+
+        // the last frame has the robot at (0, 0)
+        let raw = miniz_oxide::inflate::decompress_to_vec_zlib(&chunks[7].1[4..]).unwrap();
+        assert_eq!(raw[1..4], hsl(0.0, 0.75, 0.75));
+
+        // the delay's denominator is a u16
+        let room = Room {
+            robots,
+            grid: IVec2::new(3, 2),
+        };
+        let path = std::env::temp_dir().join("day-14-no-fps.png");
+        for fps in [0, 65536] {
+            let config = Configuration::default();
+            let error = export_apng(&path, &room, &config, 3, fps, 1).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+            let error = export_pngs(&path, &room, &config, 3, fps, 1).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        }
+        assert!(!path.exists());
+    }
+}
+
+#[cfg(feature = "bench")]
+pub mod benchmarks {
+    use super::{encode_png, render};
+    use crate::robot::{get_robots, Robot, GRID};
+
+    #[divan::bench()]
+    fn bench_render_png() {
+        let robots: Vec<Robot> = get_robots().collect();
+        encode_png(&render(&robots, 6370.0, GRID, 8));
+    }
+}
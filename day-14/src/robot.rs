@@ -0,0 +1,213 @@
+use std::fs;
+use std::path::Path;
+
+use glam::{IVec2, Vec2};
+
+const INPUT: &str = include_str!("input.txt");
+
+// the size of the bathroom the robots are in
+pub const GRID: IVec2 = IVec2::new(101, 103);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Robot {
+    pub position: IVec2,
+    pub velocity: IVec2,
+}
+
+impl Robot {
+    pub fn parse(line: &str) -> Self {
+        let line = line.strip_prefix("p=").unwrap();
+        let (position, velocity) = line.split_once(" v=").unwrap();
+        let (x, y) = position.split_once(',').unwrap();
+        let position = IVec2::new(x.parse().unwrap(), y.parse().unwrap());
+        let (x, y) = velocity.split_once(',').unwrap();
+        let velocity = IVec2::new(x.parse().unwrap(), y.parse().unwrap());
+        Robot { position, velocity }
+    }
+
+    // where the robot is after `tick` seconds, or before it when negative.
+    // Each axis repeats with the size of the grid, so the tick is wrapped
+    // first and it can be as large as it likes.
+    pub fn position_at(&self, tick: i64, grid: IVec2) -> IVec2 {
+        let axis = |position: i32, velocity: i32, size: i32| {
+            let size = size as i64;
+            (position as i64 + velocity as i64 * tick.rem_euclid(size)).rem_euclid(size) as i32
+        };
+        IVec2::new(
+            axis(self.position.x, self.velocity.x, grid.x),
+            axis(self.position.y, self.velocity.y, grid.y),
+        )
+    }
+
+    // the same for part way through a tick, for animating
+    pub fn position_at_f32(&self, tick: f32, grid: IVec2) -> Vec2 {
+        let whole = tick.floor();
+        let position = self.position_at(whole as i64, grid).as_vec2();
+        (position + self.velocity.as_vec2() * (tick - whole)).rem_euclid(grid.as_vec2())
+    }
+
+    pub fn simulate(&mut self, grid: IVec2) {
+        self.position = self.position_at(1, grid);
+    }
+}
+
+pub fn parse(input: &str) -> Vec<Robot> {
+    input.lines().map(Robot::parse).collect()
+}
+
+// the robots from the puzzle input
+pub fn get_robots() -> impl Iterator<Item = Robot> {
+    INPUT.lines().map(Robot::parse)
+}
+
+// 1 to 4 reading across then down, or 0 on the lines between them
+pub fn quadrant(position: IVec2, grid: IVec2) -> usize {
+    let mid_x = grid.x / 2;
+    let mid_y = grid.y / 2;
+
+    match (position.x, position.y) {
+        (x, y) if x < mid_x && y < mid_y => 1,
+        (x, y) if x > mid_x && y < mid_y => 2,
+        (x, y) if x < mid_x && y > mid_y => 3,
+        (x, y) if x > mid_x && y > mid_y => 4,
+        _ => 0,
+    }
+}
+
+// the robots in each quadrant, in the same order
+pub fn quadrants(positions: impl Iterator<Item = IVec2>, grid: IVec2) -> [usize; 4] {
+    let mut quadrants = [0; 4];
+    for position in positions {
+        match quadrant(position, grid) {
+            0 => {}
+            quadrant => quadrants[quadrant - 1] += 1,
+        }
+    }
+    quadrants
+}
+
+pub fn safety_factor(quadrants: [usize; 4]) -> usize {
+    quadrants.iter().product()
+}
+
+// the robots and the size of the room they're in
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Room {
+    pub robots: Vec<Robot>,
+    pub grid: IVec2,
+}
+
+impl Default for Room {
+    fn default() -> Self {
+        Self {
+            robots: get_robots().collect(),
+            grid: GRID,
+        }
+    }
+}
+
+impl Room {
+    // takes `--input PATH` and `--size WIDTHxHEIGHT` out of the arguments,
+    // leaving the rest. Without them it's the puzzle input in a room of the
+    // puzzle's size.
+    pub fn from_args(args: &mut Vec<String>) -> Result<Self, String> {
+        let mut room = Room::default();
+        let mut i = 0;
+        while i < args.len() {
+            match args[i].as_str() {
+                "--input" | "--size" if i + 1 == args.len() => {
+                    return Err(format!("{} should have a value", args[i]));
+                }
+                "--input" => {
+                    let path = args.remove(i + 1);
+                    room.robots = load(Path::new(&path))
+                        .map_err(|error| format!("can't read {path}: {error}"))?;
+                    args.remove(i);
+                }
+                "--size" => {
+                    let size = args.remove(i + 1);
+                    room.grid = parse_size(&size)
+                        .ok_or_else(|| format!("size should be like 101x103, not {size}"))?;
+                    args.remove(i);
+                }
+                _ => i += 1,
+            }
+        }
+        Ok(room)
+    }
+
+    pub fn quadrants_at(&self, tick: i64) -> [usize; 4] {
+        quadrants(
+            self.robots
+                .iter()
+                .map(|robot| robot.position_at(tick, self.grid)),
+            self.grid,
+        )
+    }
+}
+
+pub fn load(path: &Path) -> std::io::Result<Vec<Robot>> {
+    Ok(parse(&fs::read_to_string(path)?))
+}
+
+// `101x103`
+pub fn parse_size(size: &str) -> Option<IVec2> {
+    let (width, height) = size.split_once('x')?;
+    let size = IVec2::new(width.parse().ok()?, height.parse().ok()?);
+    size.cmpgt(IVec2::ZERO).all().then_some(size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_position_at() {
+        let robot = Robot::parse("p=2,4 v=2,-3");
+        let grid = IVec2::new(11, 7);
+
+        let mut stepped = robot;
+        for tick in 0..200 {
+            assert_eq!(robot.position_at(tick, grid), stepped.position, "{tick}");
+            stepped.simulate(grid);
+        }
+        assert_eq!(robot.position_at(5, grid), IVec2::new(1, 3));
+        assert_eq!(robot.position_at(-1, grid), IVec2::new(0, 0));
+        assert_eq!(
+            robot.position_at(77 * 1_000_000_000_000, grid),
+            robot.position
+        );
+        assert_eq!(
+            robot.position_at(i64::MIN, grid),
+            robot.position_at(i64::MIN % 77, grid)
+        );
+
+        assert_eq!(robot.position_at_f32(5.0, grid), Vec2::new(1.0, 3.0));
+        assert_eq!(robot.position_at_f32(4.5, grid), Vec2::new(0.0, 4.5));
+        assert_eq!(robot.position_at_f32(-0.25, grid), Vec2::new(1.5, 4.75));
+    }
+
+    #[test]
+    fn test_room_from_args() {
+        let mut args: Vec<String> = ["--size", "11x7", "--fps", "4"].map(String::from).to_vec();
+        let room = Room::from_args(&mut args).unwrap();
+        assert_eq!(room.grid, IVec2::new(11, 7));
+        assert_eq!(args, ["--fps", "4"]);
+
+        let mut args = vec!["--size".to_string(), "11 by 7".to_string()];
+        assert!(Room::from_args(&mut args).is_err());
+        let mut args = vec!["--input".to_string()];
+        assert!(Room::from_args(&mut args).is_err());
+        assert_eq!(parse_size("0x7"), None);
+    }
+
+    #[test]
+    fn test_quadrants() {
+        let room = Room {
+            robots: parse("p=0,0 v=0,0\np=5,0 v=0,0\np=10,6 v=0,0\np=10,6 v=0,0\np=8,1 v=0,0"),
+            grid: IVec2::new(11, 7),
+        };
+        assert_eq!(room.quadrants_at(0), [1, 1, 0, 2]);
+        assert_eq!(safety_factor(room.quadrants_at(0)), 0);
+    }
+}
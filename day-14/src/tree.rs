@@ -0,0 +1,271 @@
+use std::collections::VecDeque;
+
+use glam::IVec2;
+
+use crate::robot::{parse, Robot, GRID};
+
+const INPUT: &str = include_str!("input.txt");
+
+// how to tell that the robots are drawing something, rather than spread about
+// at random
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Score {
+    // the robots bunch up on each axis
+    Variance,
+    // few rows and columns hold most of the robots
+    Entropy,
+    // lots of robots are next to each other. This doesn't split into axes,
+    // so every tick until the whole picture repeats is looked at.
+    LargestCluster,
+}
+
+impl Score {
+    pub const ALL: [Score; 3] = [Score::Variance, Score::Entropy, Score::LargestCluster];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Score::Variance => "variance",
+            Score::Entropy => "entropy",
+            Score::LargestCluster => "largest cluster",
+        }
+    }
+}
+
+// the first tick that the robots draw the picture at, `None` without any
+// robots. The x positions repeat every `grid.x` ticks and the y positions every
+// `grid.y`, so the most ordered tick for each axis on its own gives the tick
+// for both by the Chinese remainder theorem.
+pub fn find_tree(robots: &[Robot], grid: IVec2, score: Score) -> Option<i64> {
+    if robots.is_empty() {
+        return None;
+    }
+
+    match score {
+        Score::Variance | Score::Entropy => {
+            let axis = |size: i32, coordinate: fn(IVec2) -> i32| {
+                let spread = |tick: i64| {
+                    let values = robots
+                        .iter()
+                        .map(|robot| coordinate(robot.position_at(tick, grid)));
+                    match score {
+                        Score::Variance => variance(values),
+                        _ => entropy(values, size),
+                    }
+                };
+                (0..size as i64).min_by(|&a, &b| spread(a).total_cmp(&spread(b)))
+            };
+            let x = axis(grid.x, |position| position.x)?;
+            let y = axis(grid.y, |position| position.y)?;
+            crt((x, grid.x as i64), (y, grid.y as i64)).map(|(tick, _)| tick)
+        }
+        Score::LargestCluster => {
+            let (_, period) = crt((0, grid.x as i64), (0, grid.y as i64))?;
+            // the earliest tick with the largest cluster
+            (0..period)
+                .rev()
+                .max_by_key(|&tick| largest_cluster(robots, tick, grid))
+        }
+    }
+}
+
+fn variance(values: impl Iterator<Item = i32>) -> f64 {
+    let (mut n, mut sum, mut squares) = (0.0, 0.0, 0.0);
+    for value in values {
+        let value = value as f64;
+        n += 1.0;
+        sum += value;
+        squares += value * value;
+    }
+    squares / n - (sum / n) * (sum / n)
+}
+
+// in bits, of how the values are spread over `0..size`
+fn entropy(values: impl Iterator<Item = i32>, size: i32) -> f64 {
+    let mut counts = vec![0usize; size as usize];
+    let mut n = 0;
+    for value in values {
+        counts[value as usize] += 1;
+        n += 1;
+    }
+    counts
+        .iter()
+        .filter(|&&count| count > 0)
+        .map(|&count| {
+            let p = count as f64 / n as f64;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+// the most robots in one group of tiles that touch side on, counting each
+// tile once however many robots are on it
+pub fn largest_cluster(robots: &[Robot], tick: i64, grid: IVec2) -> usize {
+    let index = |position: IVec2| (position.y * grid.x + position.x) as usize;
+    let mut occupied = vec![false; (grid.x * grid.y) as usize];
+    for robot in robots {
+        occupied[index(robot.position_at(tick, grid))] = true;
+    }
+
+    let mut largest = 0;
+    let mut queue = VecDeque::new();
+    for start in 0..occupied.len() {
+        if !occupied[start] {
+            continue;
+        }
+        occupied[start] = false;
+        queue.push_back(start);
+        let mut size = 0;
+        while let Some(tile) = queue.pop_front() {
+            size += 1;
+            let (x, y) = ((tile as i32) % grid.x, (tile as i32) / grid.x);
+            for (dx, dy) in [(0, -1), (1, 0), (0, 1), (-1, 0)] {
+                let next = IVec2::new(x + dx, y + dy);
+                if next.cmpge(IVec2::ZERO).all() && next.cmplt(grid).all() && occupied[index(next)]
+                {
+                    occupied[index(next)] = false;
+                    queue.push_back(index(next));
+                }
+            }
+        }
+        largest = largest.max(size);
+    }
+    largest
+}
+
+// the t with t = a mod m for both, and the period it repeats with
+fn crt((a, m): (i64, i64), (b, n): (i64, i64)) -> Option<(i64, i64)> {
+    let (g, p, _) = extended_gcd(m, n);
+    if (b - a) % g != 0 {
+        return None;
+    }
+    let lcm = m / g * n;
+    // m * p = g mod n, so stepping by m * p * (b - a) / g covers the difference
+    let step = ((b - a) / g * p).rem_euclid(n / g);
+    Some(((a + m * step).rem_euclid(lcm), lcm))
+}
+
+// (g, x, y) with a * x + b * y = g
+fn extended_gcd(a: i64, b: i64) -> (i64, i64, i64) {
+    match b {
+        0 => (a, 1, 0),
+        _ => {
+            let (g, x, y) = extended_gcd(b, a % b);
+            (g, y, x - a / b * y)
+        }
+    }
+}
+
+#[tracing::instrument(level = "trace", skip())]
+pub fn run() -> String {
+    process(INPUT, GRID)
+}
+
+#[tracing::instrument(level = "trace", skip(input))]
+fn process(input: &str, grid: IVec2) -> String {
+    let robots = parse(input);
+    let mut report = String::new();
+    for score in Score::ALL {
+        let tick = find_tree(&robots, grid, score);
+        let tick = tick.map_or("no robots".to_string(), |tick| tick.to_string());
+        report.push_str(&format!("{}: {tick}\n", score.name()));
+    }
+    report
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    // robots wandering about at random, and some more that fill a triangle
+    // at `tick`
+    pub(crate) fn robots_drawing_at(tick: i64, grid: IVec2) -> String {
+        let mut state: u64 = 2024;
+        let mut random = |n: i32| {
+            // xorshift64
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state % n as u64) as i32
+        };
+
+        let mut robots = vec![];
+        for _ in 0..120 {
+            let position = IVec2::new(random(grid.x), random(grid.y));
+            let velocity = IVec2::new(random(21) - 10, random(21) - 10);
+            robots.push(Robot { position, velocity });
+        }
+        for row in 0..10 {
+            for x in 10 - row..=10 + row {
+                let velocity = IVec2::new(random(21) - 10, random(21) - 10);
+                let at = Robot {
+                    position: IVec2::new(x, 12 + row),
+                    velocity,
+                };
+                robots.push(Robot {
+                    position: at.position_at(-tick, grid),
+                    velocity,
+                });
+            }
+        }
+
+        robots
+            .iter()
+            .map(|Robot { position, velocity }| {
+                format!(
+                    "p={},{} v={},{}\n",
+                    position.x, position.y, velocity.x, velocity.y
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn it_works() {
+        let grid = IVec2::new(31, 37);
+        assert_eq!(
+            process(&robots_drawing_at(700, grid), grid),
+            "variance: 700\nentropy: 700\nlargest cluster: 700\n"
+        );
+        assert_eq!(
+            process("", grid),
+            "variance: no robots\nentropy: no robots\nlargest cluster: no robots\n"
+        );
+    }
+
+    #[test]
+    fn test_crt() {
+        assert_eq!(crt((3, 101), (5, 103)), Some((10305, 10403)));
+        assert_eq!(crt((2, 4), (0, 6)), Some((6, 12)));
+        assert_eq!(crt((1, 4), (0, 6)), None);
+    }
+
+    #[test]
+    fn test_largest_cluster() {
+        let robots = parse("p=0,0 v=0,0\np=1,0 v=0,0\np=1,1 v=0,0\np=1,1 v=1,0\np=3,1 v=0,0");
+        assert_eq!(largest_cluster(&robots, 0, IVec2::new(5, 3)), 3);
+        // the fourth robot joins the last one on
+        assert_eq!(largest_cluster(&robots, 1, IVec2::new(5, 3)), 5);
+        // and then sits on top of it, away from the rest
+        assert_eq!(largest_cluster(&robots, 2, IVec2::new(5, 3)), 3);
+    }
+}
+
+#[cfg(feature = "bench")]
+pub mod benchmarks {
+    use super::{find_tree, parse, Score, GRID, INPUT};
+
+    #[divan::bench()]
+    fn bench_variance() {
+        find_tree(&parse(INPUT), GRID, Score::Variance);
+    }
+
+    #[divan::bench()]
+    fn bench_entropy() {
+        find_tree(&parse(INPUT), GRID, Score::Entropy);
+    }
+
+    #[divan::bench(sample_count = 10)]
+    fn bench_largest_cluster() {
+        find_tree(&parse(INPUT), GRID, Score::LargestCluster);
+    }
+}
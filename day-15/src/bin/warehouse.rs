@@ -0,0 +1,42 @@
+// replays the robot's moves and prints the warehouse after them with its GPS
+// sum, like `cargo run -p day-15 --bin warehouse -- --width 2 --step 100`.
+// `--width` stretches every cell of the map, so part 2 is width 2, and
+// without `--step` it plays every move. `--log` lists what each move did
+// first. The last argument can be a file to use instead of the puzzle input.
+fn main() {
+    let mut width = 1;
+    let mut step = None;
+    let mut log = false;
+    let mut path = None;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .unwrap_or_else(|| panic!("{arg} should have a value"))
+        };
+        match arg.as_str() {
+            "--width" => {
+                width = value()
+                    .parse()
+                    .ok()
+                    .filter(|&width| width > 0)
+                    .expect("width should be a number above 0")
+            }
+            "--log" => log = true,
+            "--step" => step = Some(value().parse().expect("step should be a number")),
+            _ => path = Some(arg),
+        }
+    }
+
+    let input = path.map(|path| {
+        std::fs::read_to_string(&path).unwrap_or_else(|error| panic!("can't read {path}: {error}"))
+    });
+    let output = match (input, log) {
+        (Some(input), false) => day_15::warehouse::process(&input, width, step),
+        (Some(input), true) => day_15::history::process(&input, width, step),
+        (None, false) => day_15::warehouse::run(width, step),
+        (None, true) => day_15::history::run(width, step),
+    };
+    print!("{output}");
+}
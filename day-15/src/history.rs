@@ -0,0 +1,197 @@
+use glam::IVec2;
+
+use crate::warehouse::{parse, Event, Warehouse};
+
+const INPUT: &str = include_str!("input.txt");
+
+// the robot's moves as a log of events, with the warehouse at some point in
+// it that can be stepped back and forth
+#[derive(Debug, Clone)]
+pub struct History {
+    warehouse: Warehouse,
+    events: Vec<Event>,
+    // how many of the events have happened to `warehouse`; the rest have
+    // been undone and can be redone
+    done: usize,
+}
+
+impl History {
+    pub fn new(warehouse: Warehouse) -> Self {
+        Self {
+            warehouse,
+            events: vec![],
+            done: 0,
+        }
+    }
+
+    // the warehouse after every one of the moves, keeping the log
+    pub fn record(warehouse: Warehouse, moves: &[IVec2]) -> Self {
+        let mut history = History::new(warehouse);
+        for &direction in moves {
+            history.step(direction);
+        }
+        history
+    }
+
+    pub fn warehouse(&self) -> &Warehouse {
+        &self.warehouse
+    }
+
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+
+    // how far through the log the warehouse is
+    pub fn position(&self) -> usize {
+        self.done
+    }
+
+    // makes a new move, forgetting anything that had been undone
+    pub fn step(&mut self, direction: IVec2) -> &Event {
+        self.events.truncate(self.done);
+        let event = self.warehouse.step(direction);
+        self.events.push(event);
+        self.done += 1;
+        &self.events[self.done - 1]
+    }
+
+    pub fn undo(&mut self) -> Option<&Event> {
+        self.done = self.done.checked_sub(1)?;
+        let event = &self.events[self.done];
+        self.warehouse.undo(event);
+        Some(event)
+    }
+
+    pub fn redo(&mut self) -> Option<&Event> {
+        let event = self.events.get(self.done)?;
+        let replayed = self.warehouse.step(event.direction());
+        debug_assert_eq!(&replayed, event);
+        self.done += 1;
+        Some(event)
+    }
+
+    // undoes or redoes until `position` events have happened
+    pub fn seek(&mut self, position: usize) {
+        let position = position.min(self.events.len());
+        while self.done > position {
+            self.undo();
+        }
+        while self.done < position {
+            self.redo();
+        }
+    }
+
+    // the map after `position` events, leaving the history where it is
+    pub fn debug_map(&self, position: usize) -> String {
+        let mut history = self.clone();
+        history.seek(position);
+        history.warehouse.to_string()
+    }
+}
+
+#[tracing::instrument(level = "trace", skip())]
+pub fn run(width: usize, step: Option<usize>) -> String {
+    process(INPUT, width, step)
+}
+
+// the log of events up to `step`, or all of them, and the map at that point
+#[tracing::instrument(level = "trace", skip(input))]
+pub fn process(input: &str, width: usize, step: Option<usize>) -> String {
+    let (warehouse, moves) = parse(input, width);
+    let history = History::record(warehouse, &moves);
+    let step = step.unwrap_or(moves.len()).min(moves.len());
+
+    let mut report = String::new();
+    for (i, event) in history.events()[..step].iter().enumerate() {
+        report += &format!("{}: {event}\n", i + 1);
+    }
+    report + &history.debug_map(step)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::warehouse::tests::{LARGER, SMALL};
+    use crate::warehouse::Direction;
+
+    #[test]
+    fn it_works() {
+        assert_eq!(
+            process(SMALL, 1, Some(3)),
+            "1: < blocked by the wall at 1,2
+2: ^ moved
+3: ^ blocked by the wall at 2,0
+########
+#.@O.O.#
+##..O..#
+#...O..#
+#.#.O..#
+#...O..#
+#......#
+########
+"
+        );
+        assert!(process(SMALL, 1, Some(4)).contains("4: > moved, shifting boxes 0\n"));
+    }
+
+    #[test]
+    fn undo_redo() {
+        for width in [1, 2, 3] {
+            let (warehouse, moves) = parse(LARGER, width);
+            let mut history = History::record(warehouse.clone(), &moves);
+            assert_eq!(history.position(), moves.len());
+
+            // every point in the log matches playing the moves from the start
+            for step in (0..=moves.len()).rev().step_by(7) {
+                history.seek(step);
+                assert_eq!(history.warehouse(), &warehouse.snapshot(&moves, step));
+            }
+            while history.undo().is_some() {}
+            assert_eq!(history.warehouse(), &warehouse);
+            assert_eq!(
+                history.debug_map(moves.len()),
+                warehouse.snapshot(&moves, moves.len()).to_string()
+            );
+            while history.redo().is_some() {}
+            assert_eq!(
+                history.warehouse(),
+                &warehouse.snapshot(&moves, moves.len())
+            );
+        }
+    }
+
+    #[test]
+    fn step_forgets_undone() {
+        let (warehouse, moves) = parse(SMALL, 1);
+        let mut history = History::record(warehouse.clone(), &moves);
+        history.seek(4);
+        assert!(history.step(Direction::SOUTH).moved());
+        assert_eq!(history.events().len(), 5);
+        assert_eq!(history.redo(), None);
+
+        let mut expected = warehouse.snapshot(&moves, 4);
+        expected.step(Direction::SOUTH);
+        assert_eq!(history.warehouse(), &expected);
+    }
+}
+
+#[cfg(feature = "bench")]
+pub mod benchmarks {
+    use super::INPUT;
+    use crate::warehouse::parse;
+
+    #[divan::bench(args = [1, 2])]
+    fn bench_record(width: usize) {
+        let (warehouse, moves) = parse(INPUT, width);
+        super::History::record(warehouse, &moves);
+    }
+
+    #[divan::bench(args = [1, 2])]
+    fn bench_undo_all(bencher: divan::Bencher, width: usize) {
+        let (warehouse, moves) = parse(INPUT, width);
+        let history = super::History::record(warehouse, &moves);
+        bencher
+            .with_inputs(|| history.clone())
+            .bench_local_values(|mut history| history.seek(0));
+    }
+}
@@ -0,0 +1,504 @@
+use std::collections::VecDeque;
+use std::fmt;
+
+use glam::IVec2;
+
+const INPUT: &str = include_str!("input.txt");
+
+pub struct Direction;
+
+impl Direction {
+    pub const NORTH: IVec2 = IVec2::NEG_Y;
+    pub const SOUTH: IVec2 = IVec2::Y;
+    pub const EAST: IVec2 = IVec2::X;
+    pub const WEST: IVec2 = IVec2::NEG_X;
+
+    pub fn from_char(c: char) -> Option<IVec2> {
+        match c {
+            '^' => Some(Direction::NORTH),
+            'v' => Some(Direction::SOUTH),
+            '<' => Some(Direction::WEST),
+            '>' => Some(Direction::EAST),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Cell {
+    Empty,
+    Wall,
+    // the index of the box covering the cell
+    Box(usize),
+}
+
+// a box covering `width` cells to the east of its position
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Crate {
+    pub position: IVec2,
+    pub width: i32,
+}
+
+impl Crate {
+    pub fn cells(&self) -> impl Iterator<Item = IVec2> {
+        let Crate { position, width } = *self;
+        (0..width).map(move |x| position + IVec2::new(x, 0))
+    }
+
+    pub fn gps(&self) -> usize {
+        (self.position.y * 100 + self.position.x) as usize
+    }
+}
+
+// what came of one of the robot's moves
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+    // the robot moved, shifting these boxes along with it
+    Moved {
+        direction: IVec2,
+        shifted: Vec<usize>,
+    },
+    Blocked {
+        direction: IVec2,
+        wall: IVec2,
+    },
+}
+
+impl Event {
+    pub fn moved(&self) -> bool {
+        matches!(self, Event::Moved { .. })
+    }
+
+    pub fn direction(&self) -> IVec2 {
+        match self {
+            Event::Moved { direction, .. } | Event::Blocked { direction, .. } => *direction,
+        }
+    }
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let arrow = |direction: IVec2| match direction {
+            Direction::NORTH => '^',
+            Direction::SOUTH => 'v',
+            Direction::WEST => '<',
+            _ => '>',
+        };
+        match self {
+            Event::Moved { direction, shifted } if shifted.is_empty() => {
+                write!(f, "{} moved", arrow(*direction))
+            }
+            Event::Moved { direction, shifted } => {
+                let boxes: Vec<String> = shifted.iter().map(usize::to_string).collect();
+                write!(
+                    f,
+                    "{} moved, shifting boxes {}",
+                    arrow(*direction),
+                    boxes.join(" ")
+                )
+            }
+            Event::Blocked { direction, wall } => {
+                write!(
+                    f,
+                    "{} blocked by the wall at {},{}",
+                    arrow(*direction),
+                    wall.x,
+                    wall.y
+                )
+            }
+        }
+    }
+}
+
+// the warehouse as a grid of cells, each knowing which box covers it, so a
+// move only looks at the cells it pushes into
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Warehouse {
+    size: IVec2,
+    cells: Vec<Cell>,
+    crates: Vec<Crate>,
+    robot: IVec2,
+}
+
+impl Warehouse {
+    // a map of `#` walls, `@` the robot, `O` boxes one cell wide and `[`
+    // to `]` boxes as wide as they're drawn, like `[]` or `[--]`
+    pub fn parse(map: &str) -> Self {
+        let size = IVec2::new(
+            map.lines().map(|line| line.len()).max().unwrap_or(0) as i32,
+            map.lines().count() as i32,
+        );
+        let mut warehouse = Warehouse {
+            size,
+            cells: vec![Cell::Empty; (size.x * size.y) as usize],
+            crates: vec![],
+            robot: IVec2::ZERO,
+        };
+        let mut robots = 0;
+
+        for (y, line) in map.lines().enumerate() {
+            let mut open = None;
+            for (x, c) in line.chars().enumerate() {
+                let pos = IVec2::new(x as i32, y as i32);
+                match c {
+                    _ if open.is_some() => {
+                        if c == ']' {
+                            let start: IVec2 = open.take().unwrap();
+                            warehouse.add_crate(Crate {
+                                position: start,
+                                width: pos.x - start.x + 1,
+                            });
+                        }
+                    }
+                    '#' => {
+                        let index = warehouse.index(pos);
+                        warehouse.cells[index] = Cell::Wall;
+                    }
+                    'O' => warehouse.add_crate(Crate {
+                        position: pos,
+                        width: 1,
+                    }),
+                    '[' => open = Some(pos),
+                    '@' => {
+                        warehouse.robot = pos;
+                        robots += 1;
+                    }
+                    '.' => {}
+                    _ => panic!("unexpected {c} at {pos}"),
+                }
+            }
+            assert!(open.is_none(), "box left open on line {y}");
+        }
+        assert_eq!(robots, 1, "there should be one robot");
+
+        warehouse
+    }
+
+    // the same map with every cell stretched to `width` cells, which makes
+    // each `O` a box that wide, as in part 2
+    pub fn parse_scaled(map: &str, width: usize) -> Self {
+        assert!(width > 0, "cells should be at least 1 wide");
+        let scaled = map
+            .lines()
+            .map(|line| {
+                line.chars()
+                    .map(|c| match c {
+                        'O' if width > 1 => format!("[{}]", "-".repeat(width - 2)),
+                        '@' => format!("@{}", ".".repeat(width - 1)),
+                        c => c.to_string().repeat(width),
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<String>>()
+            .join("\n");
+        Warehouse::parse(&scaled)
+    }
+
+    fn index(&self, pos: IVec2) -> usize {
+        (pos.y * self.size.x + pos.x) as usize
+    }
+
+    fn cell(&self, pos: IVec2) -> Cell {
+        if pos.cmplt(IVec2::ZERO).any() || pos.cmpge(self.size).any() {
+            // outside the map is as good as a wall
+            return Cell::Wall;
+        }
+        self.cells[self.index(pos)]
+    }
+
+    fn add_crate(&mut self, new: Crate) {
+        let id = self.crates.len();
+        for pos in new.cells() {
+            let index = self.index(pos);
+            self.cells[index] = Cell::Box(id);
+        }
+        self.crates.push(new);
+    }
+
+    pub fn robot(&self) -> IVec2 {
+        self.robot
+    }
+
+    pub fn crates(&self) -> &[Crate] {
+        &self.crates
+    }
+
+    // the boxes that would move if the robot walked in `direction`, found
+    // breadth first from the cell in front of it, or the first wall in the
+    // way
+    pub fn push_set(&self, direction: IVec2) -> Result<Vec<usize>, IVec2> {
+        let mut pushed = vec![];
+        let mut seen = vec![false; self.crates.len()];
+        let mut queue = VecDeque::from([self.robot + direction]);
+
+        while let Some(pos) = queue.pop_front() {
+            match self.cell(pos) {
+                Cell::Empty => {}
+                Cell::Wall => return Err(pos),
+                Cell::Box(id) if seen[id] => {}
+                Cell::Box(id) => {
+                    seen[id] = true;
+                    pushed.push(id);
+                    queue.extend(
+                        self.crates[id]
+                            .cells()
+                            .map(|cell| cell + direction)
+                            .filter(|&next| self.cell(next) != Cell::Box(id)),
+                    );
+                }
+            }
+        }
+
+        Ok(pushed)
+    }
+
+    // walks the robot one step, pushing whatever is in the way, and says
+    // what happened
+    pub fn step(&mut self, direction: IVec2) -> Event {
+        match self.push_set(direction) {
+            Ok(shifted) => {
+                self.shift(&shifted, direction);
+                Event::Moved { direction, shifted }
+            }
+            Err(wall) => Event::Blocked { direction, wall },
+        }
+    }
+
+    // puts things back the way they were before `event`, which should be
+    // the last thing that happened
+    pub fn undo(&mut self, event: &Event) {
+        if let Event::Moved { direction, shifted } = event {
+            self.shift(shifted, -*direction);
+        }
+    }
+
+    // moves the robot and the boxes without checking there's room, which
+    // there is when they came from a push set or are going back
+    fn shift(&mut self, ids: &[usize], direction: IVec2) {
+        for &id in ids {
+            for pos in self.crates[id].cells() {
+                let index = self.index(pos);
+                self.cells[index] = Cell::Empty;
+            }
+        }
+        for &id in ids {
+            self.crates[id].position += direction;
+            for pos in self.crates[id].cells() {
+                let index = self.index(pos);
+                self.cells[index] = Cell::Box(id);
+            }
+        }
+        self.robot += direction;
+    }
+
+    pub fn run(&mut self, moves: &[IVec2]) {
+        for &direction in moves {
+            self.step(direction);
+        }
+    }
+
+    // the warehouse after the first `step` moves, leaving this one as it is
+    pub fn snapshot(&self, moves: &[IVec2], step: usize) -> Warehouse {
+        let mut warehouse = self.clone();
+        warehouse.run(&moves[..step.min(moves.len())]);
+        warehouse
+    }
+
+    // the warehouse after each of the moves in turn
+    pub fn replay<'a>(&self, moves: &'a [IVec2]) -> impl Iterator<Item = Warehouse> + 'a {
+        let mut warehouse = self.clone();
+        moves.iter().map(move |&direction| {
+            warehouse.step(direction);
+            warehouse.clone()
+        })
+    }
+
+    pub fn gps_sum(&self) -> usize {
+        self.crates.iter().map(Crate::gps).sum()
+    }
+}
+
+// draws the map the way it's parsed, so it reads back the same
+impl fmt::Display for Warehouse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for y in 0..self.size.y {
+            for x in 0..self.size.x {
+                let pos = IVec2::new(x, y);
+                let c = match self.cell(pos) {
+                    _ if pos == self.robot => '@',
+                    Cell::Empty => '.',
+                    Cell::Wall => '#',
+                    Cell::Box(id) => match self.crates[id] {
+                        Crate { width: 1, .. } => 'O',
+                        Crate { position, .. } if position == pos => '[',
+                        Crate { position, width } if position.x + width - 1 == x => ']',
+                        _ => '-',
+                    },
+                };
+                write!(f, "{c}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+// the warehouse with every cell stretched to `width`, and the robot's moves
+pub fn parse(input: &str, width: usize) -> (Warehouse, Vec<IVec2>) {
+    let (map, moves) = input.split_once("\n\n").unwrap();
+    let moves = moves.chars().filter_map(Direction::from_char).collect();
+    (Warehouse::parse_scaled(map, width), moves)
+}
+
+#[tracing::instrument(level = "trace", skip())]
+pub fn run(width: usize, step: Option<usize>) -> String {
+    process(INPUT, width, step)
+}
+
+// the warehouse after `step` moves, or all of them, and its GPS sum
+#[tracing::instrument(level = "trace", skip(input))]
+pub fn process(input: &str, width: usize, step: Option<usize>) -> String {
+    let (warehouse, moves) = parse(input, width);
+    let warehouse = warehouse.snapshot(&moves, step.unwrap_or(moves.len()));
+    format!("{warehouse}gps: {}\n", warehouse.gps_sum())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    pub(crate) const SMALL: &str = "########
+#..O.O.#
+##@.O..#
+#...O..#
+#.#.O..#
+#...O..#
+#......#
+########
+
+<^^>>>vv<v>>v<<";
+
+    pub(crate) const LARGER: &str = "##########
+#..O..O.O#
+#......O.#
+#.OO..O.O#
+#..O@..O.#
+#O#..O...#
+#O..O..O.#
+#.OO.O.OO#
+#....O...#
+##########
+
+<vv>^<v^>v>^vv^v>v<>v^v<v<^vv<<<^><<><>>v<vvv<>^v^>^<<<><<v<<<v^vv^v>^
+vvv<<^>^v^^><<>>><>^<<><^vv^^<>vvv<>><^^v>^>vv<>v<<<<v<^v>^<^^>>>^<v<v
+><>vv>v^v^<>><>>>><^^>vv>v<^^^>>v^v^<^^>v^^>v^<^v>v<>>v^v^<v>v^^<^^vv<
+<<v<^>>^^^^>>>v^<>vvv^><v<<<>^^^vv^<vvv>^>v<^^^^v<>^>vvvv><>>v^<<^^^^^
+^><^><>>><>^^<<^^v>>><^<v>^<vv>>v>>>^v><>^v><<<<v>>v<v<v>vvv>^<><<>^><
+^>><>^v<><^vvv<^^<><v<<<<<><^v<<<><<<^^<v<^^^><^>>^<v^><<<^>>^v<v^v<v^
+>^>>^v>vv>^<<^v<>><<><<v<<v><>v<^vv<<<>^^v^>^^>>><<^v>>v^v><^^>>^<>vv^
+<><^^>^^^<><vvvvv^v<v<<>^v<v>v<<^><<><<><<<^^<<<^<<>><<><^^^>^^<>^>v<>
+^^>vv<^v^v<vv>^<><v<^v>^^^>>>^^vvv^>vvv<>>>^<^>>>>>^<<^v>^vvv<>^<><<v>
+v^^>>><<^^<>>^v^<v^vv<>v^<<>^<^v^v><^<<<><<^<v><v<>vv>>v><v^<vv<>v^<<^";
+
+    #[test]
+    fn it_works() {
+        let (warehouse, moves) = parse(SMALL, 1);
+        assert_eq!(warehouse.snapshot(&moves, moves.len()).gps_sum(), 2028);
+        let (warehouse, moves) = parse(LARGER, 1);
+        assert_eq!(warehouse.snapshot(&moves, moves.len()).gps_sum(), 10092);
+        let (warehouse, moves) = parse(LARGER, 2);
+        assert_eq!(warehouse.snapshot(&moves, moves.len()).gps_sum(), 9021);
+    }
+
+    #[test]
+    fn scaled_map() {
+        let (warehouse, moves) = parse(LARGER, 2);
+        assert_eq!(
+            warehouse.snapshot(&moves, moves.len()).to_string(),
+            "####################
+##[].......[].[][]##
+##[]...........[].##
+##[]........[][][]##
+##[]......[]....[]##
+##..##......[]....##
+##..[]............##
+##..@......[].[][]##
+##......[][]..[]..##
+####################
+"
+        );
+        let warehouse = Warehouse::parse_scaled("#.O@", 3);
+        assert_eq!(warehouse.to_string(), "###...[-]@..\n");
+        assert_eq!(Warehouse::parse(&warehouse.to_string()), warehouse);
+    }
+
+    #[test]
+    fn wide_pushes() {
+        // a wide box resting on two narrower ones pushes both, and a wall
+        // over any of them stops the lot
+        let blocked = "....#.
+.[--].
+..[]..
+.[]...
+..@...";
+        let mut warehouse = Warehouse::parse(blocked);
+        assert_eq!(warehouse.push_set(Direction::NORTH), Err(IVec2::new(4, 0)));
+        assert_eq!(
+            warehouse.step(Direction::NORTH),
+            Event::Blocked {
+                direction: Direction::NORTH,
+                wall: IVec2::new(4, 0)
+            }
+        );
+        assert_eq!(warehouse.to_string().trim_end(), blocked);
+
+        let mut warehouse = Warehouse::parse(&blocked.replace('#', "."));
+        assert_eq!(warehouse.push_set(Direction::NORTH).unwrap().len(), 3);
+        assert!(warehouse.step(Direction::NORTH).moved());
+        assert_eq!(
+            warehouse.to_string(),
+            ".[--].
+..[]..
+.[]...
+..@...
+......
+"
+        );
+        assert!(!warehouse.step(Direction::NORTH).moved());
+        assert!(warehouse.step(Direction::EAST).moved());
+        assert_eq!(warehouse.robot(), IVec2::new(3, 3));
+    }
+
+    #[test]
+    fn replay() {
+        let (warehouse, moves) = parse(SMALL, 1);
+        let states: Vec<Warehouse> = warehouse.replay(&moves).collect();
+        assert_eq!(states.len(), moves.len());
+        for (step, state) in states.iter().enumerate() {
+            assert_eq!(&warehouse.snapshot(&moves, step + 1), state);
+        }
+        assert_eq!(warehouse.snapshot(&moves, 0), warehouse);
+        assert_eq!(
+            states[3].to_string(),
+            "########
+#..@OO.#
+##..O..#
+#...O..#
+#.#.O..#
+#...O..#
+#......#
+########
+"
+        );
+    }
+}
+
+#[cfg(feature = "bench")]
+pub mod benchmarks {
+    use super::INPUT;
+
+    #[divan::bench(args = [1, 2, 4])]
+    fn bench_process(width: usize) {
+        super::process(INPUT, width, None);
+    }
+}
@@ -0,0 +1,53 @@
+// solves a maze with its own move costs and markers, printing the cheapest
+// cost, how many tiles are on a cheapest path and the maze with one of them
+// drawn, like `cargo run -p day-16 --bin maze -- --turn 10 --diagonal 2 maze.txt`.
+// `--forward`, `--turn`, `--u-turn` and `--diagonal` set the costs,
+// `--start` and `--end` the markers and `--heading` which way the reindeer
+// starts facing. Without a file it solves the puzzle input.
+use day_16::maze::{parse_heading, report, Costs, Maze};
+use grid::Direction;
+
+const INPUT: &str = include_str!("../input.txt");
+
+fn main() {
+    let mut costs = Costs::default();
+    let mut start = 'S';
+    let mut end = 'E';
+    let mut heading = Direction::E;
+    let mut path = None;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .unwrap_or_else(|| panic!("{arg} should have a value"))
+        };
+        let mut cost = || -> usize { value().parse().expect("costs should be numbers") };
+        match arg.as_str() {
+            "--forward" => costs.forward = cost(),
+            "--turn" => costs.turn = cost(),
+            "--u-turn" => costs.u_turn = cost(),
+            "--diagonal" => costs.diagonal = Some(cost()),
+            "--start" => start = value().chars().next().expect("start should be a marker"),
+            "--end" => end = value().chars().next().expect("end should be a marker"),
+            "--heading" => {
+                heading = parse_heading(&value()).expect("heading should be like N or SE")
+            }
+            _ => path = Some(arg),
+        }
+    }
+
+    let input = match path {
+        Some(path) => std::fs::read_to_string(&path)
+            .unwrap_or_else(|error| panic!("can't read {path}: {error}")),
+        None => INPUT.to_string(),
+    };
+    let maze = match Maze::with_markers(&input, start, end) {
+        Ok(maze) => maze.with_costs(costs).with_heading(heading),
+        Err(error) => {
+            eprintln!("{error}");
+            std::process::exit(1);
+        }
+    };
+    print!("{}", report(&maze));
+}
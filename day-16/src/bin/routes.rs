@@ -0,0 +1,17 @@
+// lists the k cheapest routes through a maze with their costs and turns, like
+// `cargo run -p day-16 --bin routes -- 10 maze.txt`. Without a file it uses
+// the puzzle input.
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let k = args
+        .next()
+        .map_or(5, |k| k.parse().expect("k should be a number"));
+    match args.next() {
+        Some(path) => {
+            let input = std::fs::read_to_string(&path)
+                .unwrap_or_else(|error| panic!("can't read {path}: {error}"));
+            print!("{}", day_16::routes::run_with(&input, k));
+        }
+        None => print!("{}", day_16::routes::run(k)),
+    }
+}
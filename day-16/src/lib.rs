@@ -1,3 +1,5 @@
+pub mod maze;
 pub mod part1;
 pub mod part2;
 pub mod part2_pathfinding;
+pub mod routes;
@@ -0,0 +1,618 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fmt;
+
+use grid::{Direction, Grid, Vec2};
+
+const INPUT: &str = include_str!("input.txt");
+
+// in clockwise order, so the number of eighths between two of them is how far
+// apart they are
+const HEADINGS: [Direction; 8] = [
+    Direction::N,
+    Direction::NE,
+    Direction::E,
+    Direction::SE,
+    Direction::S,
+    Direction::SW,
+    Direction::W,
+    Direction::NW,
+];
+
+fn eighth(direction: Direction) -> usize {
+    HEADINGS.iter().position(|&d| d == direction).unwrap()
+}
+
+fn is_diagonal(direction: Direction) -> bool {
+    eighth(direction) % 2 == 1
+}
+
+pub fn parse_heading(heading: &str) -> Option<Direction> {
+    let names = ["N", "NE", "E", "SE", "S", "SW", "W", "NW"];
+    let index = names
+        .iter()
+        .position(|name| name.eq_ignore_ascii_case(heading))?;
+    Some(HEADINGS[index])
+}
+
+// what it costs to step to a neighbouring tile, on top of turning to face it
+// first
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Costs {
+    pub forward: usize,
+    // a quarter turn. Turning by an eighth costs half of it and by three
+    // eighths one and a half.
+    pub turn: usize,
+    // turning right round
+    pub u_turn: usize,
+    // a step to a diagonal neighbour, or None when they're out of reach
+    pub diagonal: Option<usize>,
+}
+
+// the reindeer olympics' rules
+impl Default for Costs {
+    fn default() -> Self {
+        Self {
+            forward: 1,
+            turn: 1000,
+            u_turn: 2000,
+            diagonal: None,
+        }
+    }
+}
+
+impl Costs {
+    pub fn directions(&self) -> impl Iterator<Item = Direction> {
+        let diagonals = self.diagonal.is_some();
+        HEADINGS
+            .into_iter()
+            .filter(move |&direction| diagonals || !is_diagonal(direction))
+    }
+
+    // the cost of stepping towards `direction` while facing `heading`
+    pub fn step(&self, heading: Direction, direction: Direction) -> Option<usize> {
+        let step = if is_diagonal(direction) {
+            self.diagonal?
+        } else {
+            self.forward
+        };
+        let eighths = (eighth(direction) + 8 - eighth(heading)) % 8;
+        let turn = match eighths.min(8 - eighths) {
+            0 => 0,
+            1 => self.turn / 2,
+            2 => self.turn,
+            3 => self.turn + self.turn / 2,
+            _ => self.u_turn,
+        };
+        Some(step + turn)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MazeError {
+    MissingMarker(char),
+    DuplicateStart(char),
+    Unreachable,
+}
+
+impl fmt::Display for MazeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MazeError::MissingMarker(marker) => write!(f, "there's no {marker} in the maze"),
+            MazeError::DuplicateStart(marker) => {
+                write!(f, "there should be only one {marker} to start from")
+            }
+            MazeError::Unreachable => write!(f, "the end can't be reached"),
+        }
+    }
+}
+
+impl std::error::Error for MazeError {}
+
+// a tile and the way the reindeer is facing on it
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct State {
+    pub tile: Vec2,
+    pub heading: Direction,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Solution {
+    pub cost: usize,
+    // every tile on one of the cheapest paths, in reading order
+    pub tiles: Vec<Vec2>,
+    // one of those paths, from the start to an end
+    pub path: Vec<State>,
+}
+
+#[derive(Debug)]
+pub struct Maze<'a> {
+    grid: Grid<'a>,
+    start: Vec2,
+    ends: Vec<Vec2>,
+    heading: Direction,
+    costs: Costs,
+}
+
+impl<'a> Maze<'a> {
+    // walls are `#`, and anything else is open
+    pub fn parse(input: &'a str) -> Result<Self, MazeError> {
+        Maze::with_markers(input, 'S', 'E')
+    }
+
+    // starting from the one `start` tile facing east, and finishing at any of
+    // the `end` tiles
+    pub fn with_markers(input: &'a str, start: char, end: char) -> Result<Self, MazeError> {
+        if input.trim().is_empty() {
+            return Err(MazeError::MissingMarker(start));
+        }
+        let grid = Grid::new(input.trim());
+        let find = |marker: char| {
+            input
+                .trim()
+                .char_indices()
+                .filter(move |&(_, c)| c == marker)
+                .map(|(index, _)| grid.index_to_vec2(index))
+        };
+
+        let mut starts = find(start);
+        let first = starts.next().ok_or(MazeError::MissingMarker(start))?;
+        if starts.next().is_some() {
+            return Err(MazeError::DuplicateStart(start));
+        }
+        let ends: Vec<Vec2> = find(end).collect();
+        if ends.is_empty() {
+            return Err(MazeError::MissingMarker(end));
+        }
+
+        Ok(Self {
+            grid,
+            start: first,
+            ends,
+            heading: Direction::E,
+            costs: Costs::default(),
+        })
+    }
+
+    pub fn with_costs(mut self, costs: Costs) -> Self {
+        self.costs = costs;
+        self
+    }
+
+    pub fn with_heading(mut self, heading: Direction) -> Self {
+        self.heading = heading;
+        self
+    }
+
+    pub fn costs(&self) -> &Costs {
+        &self.costs
+    }
+
+    pub fn start(&self) -> State {
+        State {
+            tile: self.start.clone(),
+            heading: self.heading,
+        }
+    }
+
+    pub fn is_end(&self, tile: &Vec2) -> bool {
+        self.ends.contains(tile)
+    }
+
+    pub fn is_open(&self, tile: &Vec2) -> bool {
+        self.grid.get(tile).is_some_and(|c| c != '#')
+    }
+
+    // the states one step on from `state`, and what each step costs
+    pub fn neighbours(&self, state: &State) -> impl Iterator<Item = (State, usize)> + '_ {
+        let State { tile, heading } = state.clone();
+        self.costs.directions().filter_map(move |direction| {
+            let next = &tile + direction;
+            let cost = self.costs.step(heading, direction)?;
+            self.is_open(&next).then_some((
+                State {
+                    tile: next,
+                    heading: direction,
+                },
+                cost,
+            ))
+        })
+    }
+
+    // the states one step back from `state`, and what each step costs, for
+    // searching from the ends
+    pub(crate) fn predecessors(&self, state: &State) -> impl Iterator<Item = (State, usize)> + '_ {
+        let State { tile, heading } = state.clone();
+        let previous = &tile + -Vec2::from(heading);
+        let open = self.is_open(&previous);
+        HEADINGS
+            .into_iter()
+            .filter(move |_| open)
+            .filter_map(move |from| {
+                let cost = self.costs.step(from, heading)?;
+                Some((
+                    State {
+                        tile: previous.clone(),
+                        heading: from,
+                    },
+                    cost,
+                ))
+            })
+    }
+
+    // states are numbered by the grid's index of their tile and their heading
+    pub(crate) fn id(&self, state: &State) -> usize {
+        self.grid.point_to_index(&state.tile).unwrap() * 8 + eighth(state.heading)
+    }
+
+    pub(crate) fn state(&self, id: usize) -> State {
+        State {
+            tile: self.grid.index_to_vec2(id / 8),
+            heading: HEADINGS[id % 8],
+        }
+    }
+
+    // how many ids there are
+    pub(crate) fn states(&self) -> usize {
+        (self.grid.cols + 1) * self.grid.rows * 8
+    }
+
+    // Dijkstra over the states, keeping every predecessor that reaches a
+    // state at its lowest cost
+    fn explore(&self) -> (Vec<usize>, Vec<Vec<usize>>) {
+        let mut costs = vec![usize::MAX; self.states()];
+        let mut parents: Vec<Vec<usize>> = vec![vec![]; self.states()];
+        let mut queue = BinaryHeap::new();
+
+        let start = self.id(&self.start());
+        costs[start] = 0;
+        queue.push(Reverse((0, start)));
+
+        while let Some(Reverse((cost, id))) = queue.pop() {
+            if cost > costs[id] {
+                continue;
+            }
+            for (next, step) in self.neighbours(&self.state(id)) {
+                let next = self.id(&next);
+                let next_cost = cost + step;
+                if next_cost < costs[next] {
+                    costs[next] = next_cost;
+                    parents[next] = vec![id];
+                    queue.push(Reverse((next_cost, next)));
+                } else if next_cost == costs[next] {
+                    parents[next].push(id);
+                }
+            }
+        }
+
+        (costs, parents)
+    }
+
+    pub fn solve(&self) -> Result<Solution, MazeError> {
+        let (costs, parents) = self.explore();
+
+        let ends: Vec<usize> = self
+            .ends
+            .iter()
+            .flat_map(|end| {
+                let index = self.grid.point_to_index(end).unwrap();
+                (0..8).map(move |heading| index * 8 + heading)
+            })
+            .collect();
+        let cost = ends
+            .iter()
+            .map(|&id| costs[id])
+            .min()
+            .filter(|&cost| cost != usize::MAX)
+            .ok_or(MazeError::Unreachable)?;
+        let ends: Vec<usize> = ends.into_iter().filter(|&id| costs[id] == cost).collect();
+
+        // walking back over every parent finds the tiles of all the cheapest
+        // paths, and the first parent each time gives one of them
+        let mut seen = vec![false; costs.len()];
+        let mut stack = ends.clone();
+        while let Some(id) = stack.pop() {
+            if !std::mem::replace(&mut seen[id], true) {
+                stack.extend(&parents[id]);
+            }
+        }
+        let mut tiles: Vec<usize> = (0..seen.len())
+            .filter(|&id| seen[id])
+            .map(|id| id / 8)
+            .collect();
+        tiles.dedup();
+        let tiles = tiles
+            .into_iter()
+            .map(|index| self.grid.index_to_vec2(index))
+            .collect();
+
+        // every state but the start first got its cost from a parent that was
+        // settled before it, so this can't loop, even when steps are free.
+        // The start can have parents of its own when there's a free way round
+        // back to it.
+        let start = self.id(&self.start());
+        let mut path = vec![ends[0]];
+        while path[path.len() - 1] != start {
+            path.push(parents[path[path.len() - 1]][0]);
+        }
+        let path = path.into_iter().rev().map(|id| self.state(id)).collect();
+
+        Ok(Solution { cost, tiles, path })
+    }
+
+    // the maze with the solution's tiles as `O`, and its path drawn with the
+    // way it was heading, leaving the markers as they are
+    pub fn draw(&self, solution: &Solution) -> String {
+        let mut rows: Vec<Vec<char>> = (0..self.grid.rows as i32)
+            .map(|y| {
+                (0..self.grid.cols as i32)
+                    .map(|x| self.grid.get(&Vec2::new(x, y)).unwrap())
+                    .collect()
+            })
+            .collect();
+        let mut mark = |tile: &Vec2, c: char| {
+            let cell = &mut rows[tile.y as usize][tile.x as usize];
+            if matches!(*cell, '.' | 'O') {
+                *cell = c;
+            }
+        };
+        for tile in &solution.tiles {
+            mark(tile, 'O');
+        }
+        for State { tile, heading } in &solution.path {
+            let arrow = match heading {
+                Direction::N => '^',
+                Direction::E => '>',
+                Direction::S => 'v',
+                Direction::W => '<',
+                Direction::NE | Direction::SW => '/',
+                Direction::SE | Direction::NW => '\\',
+            };
+            mark(tile, arrow);
+        }
+        rows.into_iter()
+            .map(|row| row.into_iter().collect::<String>() + "\n")
+            .collect()
+    }
+}
+
+#[tracing::instrument(level = "trace", skip())]
+pub fn run(costs: Costs) -> String {
+    process(INPUT, costs)
+}
+
+#[tracing::instrument(level = "trace", skip(input))]
+pub fn process(input: &str, costs: Costs) -> String {
+    match Maze::parse(input) {
+        Ok(maze) => report(&maze.with_costs(costs)),
+        Err(error) => error.to_string(),
+    }
+}
+
+// the cheapest cost, how many tiles are on a cheapest path, and the maze
+// with one of them drawn
+pub fn report(maze: &Maze) -> String {
+    match maze.solve() {
+        Ok(solution) => format!(
+            "cost: {}\ntiles: {}\n{}",
+            solution.cost,
+            solution.tiles.len(),
+            maze.draw(&solution)
+        ),
+        Err(error) => error.to_string(),
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::collections::{HashSet, VecDeque};
+
+    use super::*;
+
+    pub(crate) const EXAMPLE_1: &str = "###############
+#.......#....E#
+#.#.###.#.###.#
+#.....#.#...#.#
+#.###.#####.#.#
+#.#.#.......#.#
+#.#.#####.###.#
+#...........#.#
+###.#.#####.#.#
+#...#.....#.#.#
+#.#.#.###.#.#.#
+#.....#...#.#.#
+#.###.#.#.#.#.#
+#S..#.....#...#
+###############";
+
+    pub(crate) const EXAMPLE_2: &str = "#################
+#...#...#...#..E#
+#.#.#.#.#.#.#.#.#
+#.#.#.#...#...#.#
+#.#.#.#.###.#.#.#
+#...#.#.#.....#.#
+#.#.#.#.#.#####.#
+#.#...#.#.#.....#
+#.#.#####.#.###.#
+#.#.#.......#...#
+#.#.###.#####.###
+#.#.#...#.....#.#
+#.#.#.#####.###.#
+#.#.#.........#.#
+#.#.#.#########.#
+#S#.............#
+#################";
+
+    // the path has to be made of steps the maze allows, and add up to the
+    // cost
+    fn check_path(maze: &Maze, solution: &Solution) {
+        assert_eq!(solution.path[0], maze.start());
+        assert!(maze.is_end(&solution.path.last().unwrap().tile));
+        let cost: usize = solution
+            .path
+            .windows(2)
+            .map(|step| {
+                maze.neighbours(&step[0])
+                    .find(|(next, _)| *next == step[1])
+                    .expect("each step should be to a neighbour")
+                    .1
+            })
+            .sum();
+        assert_eq!(cost, solution.cost);
+        let tiles: HashSet<&Vec2> = solution.tiles.iter().collect();
+        assert!(solution
+            .path
+            .iter()
+            .all(|state| tiles.contains(&state.tile)));
+    }
+
+    #[test]
+    fn it_works() {
+        for (input, cost, tiles) in [(EXAMPLE_1, 7036, 45), (EXAMPLE_2, 11048, 64)] {
+            let maze = Maze::parse(input).unwrap();
+            let solution = maze.solve().unwrap();
+            assert_eq!(solution.cost, cost);
+            assert_eq!(solution.tiles.len(), tiles);
+            check_path(&maze, &solution);
+        }
+    }
+
+    #[test]
+    fn markers() {
+        // going back from the end to the start
+        let swapped = EXAMPLE_1
+            .replace('S', "X")
+            .replace('E', "S")
+            .replace('X', "E");
+        let maze = Maze::with_markers(&swapped, 'S', 'E')
+            .unwrap()
+            .with_heading(Direction::S);
+        let solution = maze.solve().unwrap();
+        assert_eq!(solution.cost, 7036 - 1000);
+        check_path(&maze, &solution);
+
+        // any of several ends will do
+        let maze = Maze::with_markers("#####\n#a.b#\n#.#.#\n#b..#\n#####", 'a', 'b').unwrap();
+        let solution = maze.solve().unwrap();
+        assert_eq!(solution.cost, 2);
+        assert_eq!(
+            solution.tiles,
+            [Vec2::new(1, 1), Vec2::new(2, 1), Vec2::new(3, 1)]
+        );
+
+        assert_eq!(
+            Maze::with_markers(EXAMPLE_1, 'A', 'E').unwrap_err(),
+            MazeError::MissingMarker('A')
+        );
+        assert_eq!(
+            Maze::with_markers(EXAMPLE_1, '#', 'E').unwrap_err(),
+            MazeError::DuplicateStart('#')
+        );
+        for empty in ["", " \n\n"] {
+            assert_eq!(
+                Maze::parse(empty).unwrap_err(),
+                MazeError::MissingMarker('S')
+            );
+        }
+        let walled = "#####\n#S#E#\n#####";
+        assert_eq!(
+            Maze::parse(walled).unwrap().solve().unwrap_err(),
+            MazeError::Unreachable
+        );
+    }
+
+    #[test]
+    fn costs() {
+        // with turning free, the cost is the length of the shortest path
+        let free = Costs {
+            forward: 1,
+            turn: 0,
+            u_turn: 0,
+            diagonal: None,
+        };
+        for input in [EXAMPLE_1, EXAMPLE_2] {
+            let maze = Maze::parse(input).unwrap().with_costs(free);
+            let solution = maze.solve().unwrap();
+            check_path(&maze, &solution);
+
+            let mut steps = vec![usize::MAX; input.len()];
+            let mut queue = VecDeque::from([(maze.start().tile, 0)]);
+            while let Some((tile, step)) = queue.pop_front() {
+                let index = maze.grid.point_to_index(&tile).unwrap();
+                if steps[index] <= step {
+                    continue;
+                }
+                steps[index] = step;
+                for direction in [Direction::N, Direction::E, Direction::S, Direction::W] {
+                    let next = &tile + direction;
+                    if maze.is_open(&next) {
+                        queue.push_back((next, step + 1));
+                    }
+                }
+            }
+            let end = &maze.ends[0];
+            assert_eq!(solution.cost, steps[maze.grid.point_to_index(end).unwrap()]);
+        }
+
+        // a U-turn cheaper than two quarter turns is worth taking
+        let maze = Maze::parse("#####\n#E.S#\n#####").unwrap();
+        assert_eq!(maze.solve().unwrap().cost, 2002);
+        let cheap = Costs {
+            u_turn: 10,
+            ..Costs::default()
+        };
+        assert_eq!(maze.with_costs(cheap).solve().unwrap().cost, 12);
+
+        // free steps and U-turns make cycles back to the start that cost
+        // nothing
+        let maze = Maze::parse("#.S.E#").unwrap().with_costs(Costs {
+            forward: 0,
+            u_turn: 0,
+            ..Costs::default()
+        });
+        let solution = maze.solve().unwrap();
+        assert_eq!(solution.cost, 0);
+        check_path(&maze, &solution);
+        assert_eq!(solution.path.len(), 3);
+    }
+
+    #[test]
+    fn diagonals() {
+        let open = "#######
+#....E#
+#.....#
+#.....#
+#S....#
+#######";
+        let costs = Costs {
+            diagonal: Some(1),
+            ..Costs::default()
+        };
+        let maze = Maze::parse(open).unwrap().with_costs(costs);
+        let solution = maze.solve().unwrap();
+        // a step east, then an eighth of a turn and three steps up the
+        // diagonal
+        assert_eq!(solution.cost, 1 + 500 + 3);
+        check_path(&maze, &solution);
+        assert_eq!(
+            maze.draw(&solution),
+            "#######
+#....E#
+#.../.#
+#../..#
+#S>...#
+#######
+"
+        );
+    }
+}
+
+#[cfg(feature = "bench")]
+pub mod benchmarks {
+    use super::{Costs, INPUT};
+
+    #[divan::bench()]
+    fn bench_process() {
+        super::process(INPUT, Costs::default());
+    }
+}
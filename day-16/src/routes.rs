@@ -0,0 +1,368 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashSet};
+
+use crate::maze::{Maze, MazeError, State};
+
+const INPUT: &str = include_str!("input.txt");
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Route {
+    pub cost: usize,
+    // how many times the reindeer changes heading along the way
+    pub turns: usize,
+    pub path: Vec<State>,
+}
+
+impl Route {
+    fn new(maze: &Maze, path: Vec<State>) -> Self {
+        let cost = path_cost(maze, &path);
+        let turns = path
+            .windows(2)
+            .filter(|step| step[0].heading != step[1].heading)
+            .count();
+        Self { cost, turns, path }
+    }
+}
+
+// every step is to the tile the reindeer turns to face, so its cost only
+// depends on the two headings
+fn path_cost(maze: &Maze, path: &[State]) -> usize {
+    path.windows(2)
+        .map(|step| {
+            maze.costs()
+                .step(step[0].heading, step[1].heading)
+                .expect("each step should be one the costs allow")
+        })
+        .sum()
+}
+
+// the cheapest cost from every state to an end, by Dijkstra backwards from
+// all of the ends at once
+fn to_end(maze: &Maze) -> Vec<usize> {
+    let ends: Vec<usize> = (0..maze.states())
+        .filter(|&id| maze.is_end(&maze.state(id).tile))
+        .collect();
+    let mut costs = vec![usize::MAX; maze.states()];
+    for &id in &ends {
+        costs[id] = 0;
+    }
+    let mut queue: BinaryHeap<_> = ends.into_iter().map(|id| Reverse((0, id))).collect();
+
+    while let Some(Reverse((cost, id))) = queue.pop() {
+        if cost > costs[id] {
+            continue;
+        }
+        for (previous, step) in maze.predecessors(&maze.state(id)) {
+            let previous = maze.id(&previous);
+            if cost + step < costs[previous] {
+                costs[previous] = cost + step;
+                queue.push(Reverse((cost + step, previous)));
+            }
+        }
+    }
+
+    costs
+}
+
+// A* towards the ends, guided by what each state costs to finish from with
+// nothing banned. Banning states and cutting steps only makes that dearer, so
+// it never overestimates and the first end reached is the cheapest. The
+// buffers are kept between searches, and only what a search touched is reset.
+struct Search<'m, 'a> {
+    maze: &'m Maze<'a>,
+    to_end: Vec<usize>,
+    // states no route may go through
+    banned: Vec<bool>,
+    costs: Vec<usize>,
+    parents: Vec<usize>,
+    touched: Vec<usize>,
+    queue: BinaryHeap<Reverse<(usize, usize, usize)>>,
+}
+
+impl<'m, 'a> Search<'m, 'a> {
+    fn new(maze: &'m Maze<'a>) -> Self {
+        Self {
+            maze,
+            to_end: to_end(maze),
+            banned: vec![false; maze.states()],
+            costs: vec![usize::MAX; maze.states()],
+            parents: vec![usize::MAX; maze.states()],
+            touched: vec![],
+            queue: BinaryHeap::new(),
+        }
+    }
+
+    // the cheapest way from `start` to an end that doesn't go through a
+    // banned state or take its first step to any of the `cut` states
+    fn cheapest(&mut self, start: usize, cut: &[usize]) -> Option<Vec<usize>> {
+        let maze = self.maze;
+        for id in self.touched.drain(..) {
+            self.costs[id] = usize::MAX;
+        }
+        self.queue.clear();
+
+        if self.to_end[start] == usize::MAX {
+            return None;
+        }
+        self.costs[start] = 0;
+        self.touched.push(start);
+        // among equally good states, the one closest to an end goes first
+        self.queue
+            .push(Reverse((self.to_end[start], self.to_end[start], start)));
+
+        while let Some(Reverse((estimate, _, id))) = self.queue.pop() {
+            let cost = self.costs[id];
+            if estimate > cost + self.to_end[id] {
+                continue;
+            }
+            let state = maze.state(id);
+            if maze.is_end(&state.tile) {
+                let mut path = vec![id];
+                while path[path.len() - 1] != start {
+                    path.push(self.parents[path[path.len() - 1]]);
+                }
+                path.reverse();
+                return Some(path);
+            }
+            for (next, step) in maze.neighbours(&state) {
+                let next = maze.id(&next);
+                if self.to_end[next] == usize::MAX
+                    || self.banned[next]
+                    || (id == start && cut.contains(&next))
+                {
+                    continue;
+                }
+                if cost + step < self.costs[next] {
+                    if self.costs[next] == usize::MAX {
+                        self.touched.push(next);
+                    }
+                    self.costs[next] = cost + step;
+                    self.parents[next] = id;
+                    let estimate = cost + step + self.to_end[next];
+                    self.queue
+                        .push(Reverse((estimate, self.to_end[next], next)));
+                }
+            }
+        }
+
+        None
+    }
+}
+
+// Yen's algorithm over the maze's (tile, heading) states: each route after
+// the first branches off one already found at some state, with the steps the
+// others took from there cut and the states before it banned, so every route
+// is different and none of them loops. As in Lawler's version, a route only
+// branches from where it left the one it came from, since the routes before
+// that are made when the last route to leave there is found.
+pub fn k_best(maze: &Maze, k: usize) -> Vec<Route> {
+    let mut search = Search::new(maze);
+    let mut found: Vec<Vec<usize>> = vec![];
+    let mut candidates = BinaryHeap::new();
+    let mut seen = HashSet::new();
+
+    if let Some(path) = search.cheapest(maze.id(&maze.start()), &[]) {
+        let states: Vec<State> = path.iter().map(|&id| maze.state(id)).collect();
+        seen.insert(path.clone());
+        candidates.push(Reverse((path_cost(maze, &states), path, 0)));
+    }
+
+    while found.len() < k {
+        let Some(Reverse((_, path, branch))) = candidates.pop() else {
+            break;
+        };
+
+        for &id in &path[..branch] {
+            search.banned[id] = true;
+        }
+        for spur in branch..path.len() - 1 {
+            let root = &path[..=spur];
+            let cut: Vec<usize> = found
+                .iter()
+                .chain([&path])
+                .filter(|other| other.len() > spur + 1 && other[..=spur] == *root)
+                .map(|other| other[spur + 1])
+                .collect();
+
+            if let Some(rest) = search.cheapest(path[spur], &cut) {
+                let candidate: Vec<usize> = root.iter().chain(&rest[1..]).copied().collect();
+                if seen.insert(candidate.clone()) {
+                    let states: Vec<State> = candidate.iter().map(|&id| maze.state(id)).collect();
+                    candidates.push(Reverse((path_cost(maze, &states), candidate, spur)));
+                }
+            }
+            // the next spur's root goes through this state
+            search.banned[path[spur]] = true;
+        }
+        for &id in &path {
+            search.banned[id] = false;
+        }
+
+        found.push(path);
+    }
+
+    found
+        .into_iter()
+        .map(|path| Route::new(maze, path.into_iter().map(|id| maze.state(id)).collect()))
+        .collect()
+}
+
+#[tracing::instrument(level = "trace", skip())]
+pub fn run(k: usize) -> String {
+    match process(INPUT, k) {
+        Ok(result) => result,
+        Err(error) => error.to_string(),
+    }
+}
+
+// for mazes that aren't the puzzle input
+#[tracing::instrument(level = "trace", skip(input))]
+pub fn run_with(input: &str, k: usize) -> String {
+    match process(input, k) {
+        Ok(result) => result,
+        Err(error) => error.to_string(),
+    }
+}
+
+#[tracing::instrument(level = "trace", skip(input))]
+fn process(input: &str, k: usize) -> Result<String, MazeError> {
+    let maze = Maze::parse(input)?;
+    let routes = k_best(&maze, k);
+    let Some(best) = routes.first().map(|route| route.cost) else {
+        // asking for no routes isn't the same as there being none
+        return match k {
+            0 => Ok(String::new()),
+            _ => Err(MazeError::Unreachable),
+        };
+    };
+    let mut report = String::new();
+    for (i, route) in routes.iter().enumerate() {
+        report += &format!(
+            "route {}: cost {} (+{}), {} turns, {} steps\n",
+            i + 1,
+            route.cost,
+            route.cost - best,
+            route.turns,
+            route.path.len() - 1
+        );
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::tests::{EXAMPLE_1, EXAMPLE_2};
+    use crate::maze::Costs;
+
+    fn check(maze: &Maze, routes: &[Route]) {
+        assert!(routes.windows(2).all(|pair| pair[0].cost <= pair[1].cost));
+        let distinct: HashSet<&Vec<State>> = routes.iter().map(|route| &route.path).collect();
+        assert_eq!(distinct.len(), routes.len());
+        for route in routes {
+            assert_eq!(route.path[0], maze.start());
+            assert!(maze.is_end(&route.path.last().unwrap().tile));
+            let states: HashSet<&State> = route.path.iter().collect();
+            assert_eq!(states.len(), route.path.len(), "routes shouldn't loop");
+        }
+    }
+
+    #[test]
+    fn it_works() {
+        for (input, tiles) in [(EXAMPLE_1, 45), (EXAMPLE_2, 64)] {
+            let maze = Maze::parse(input).unwrap();
+            let solution = maze.solve().unwrap();
+            let routes = k_best(&maze, 10);
+            assert_eq!(routes.len(), 10);
+            check(&maze, &routes);
+            assert_eq!(routes[0].cost, solution.cost);
+
+            // the cheapest routes cover the same tiles as the solver finds
+            let best: HashSet<_> = routes
+                .iter()
+                .filter(|route| route.cost == solution.cost)
+                .flat_map(|route| route.path.iter().map(|state| state.tile.clone()))
+                .collect();
+            assert_eq!(best.len(), tiles);
+        }
+
+        let report = process(EXAMPLE_1, 4).unwrap();
+        assert_eq!(report.lines().count(), 4);
+        assert!(report.starts_with("route 1: cost 7036 (+0), 7 turns, 36 steps\n"));
+    }
+
+    // the costs of every loopless route through a maze that costs no more
+    // than `limit`, cheapest first
+    fn every_route(maze: &Maze, limit: usize) -> Vec<usize> {
+        fn walk(
+            maze: &Maze,
+            path: &mut Vec<State>,
+            cost: usize,
+            limit: usize,
+            costs: &mut Vec<usize>,
+        ) {
+            let last = path.last().unwrap().clone();
+            if maze.is_end(&last.tile) {
+                costs.push(cost);
+                return;
+            }
+            for (next, step) in maze.neighbours(&last) {
+                if cost + step <= limit && !path.contains(&next) {
+                    path.push(next);
+                    walk(maze, path, cost + step, limit, costs);
+                    path.pop();
+                }
+            }
+        }
+        let mut costs = vec![];
+        walk(maze, &mut vec![maze.start()], 0, limit, &mut costs);
+        costs.sort();
+        costs
+    }
+
+    #[test]
+    fn matches_every_route() {
+        let input = "#######
+#...#E#
+#.#.#.#
+#S....#
+#######";
+        let diagonal = Costs {
+            diagonal: Some(2),
+            turn: 10,
+            ..Costs::default()
+        };
+        for costs in [Costs::default(), diagonal] {
+            let maze = Maze::parse(input).unwrap().with_costs(costs);
+            let routes = k_best(&maze, 12);
+            assert_eq!(routes.len(), 12);
+            check(&maze, &routes);
+
+            // ties with the last route can go either way
+            let found: Vec<usize> = routes.iter().map(|route| route.cost).collect();
+            let limit = found[found.len() - 1];
+            let expected = every_route(&maze, limit);
+            assert_eq!(found, expected[..found.len()]);
+            assert!(expected[found.len()..].iter().all(|&cost| cost == limit));
+        }
+
+        let walled = Maze::parse("#####\n#S#E#\n#####").unwrap();
+        assert_eq!(k_best(&walled, 3), []);
+        assert_eq!(
+            process("#####\n#S#E#\n#####", 1),
+            Err(MazeError::Unreachable)
+        );
+        assert_eq!(process("#####\n#S#E#\n#####", 0), Ok(String::new()));
+        assert_eq!(process(input, 0), Ok(String::new()));
+    }
+}
+
+#[cfg(feature = "bench")]
+pub mod benchmarks {
+    use super::INPUT;
+
+    #[divan::bench(args = [1, 5])]
+    fn bench_process(k: usize) {
+        super::process(INPUT, k).unwrap();
+    }
+}
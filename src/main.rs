@@ -15,6 +15,11 @@ struct Args {
     /// Which part to run
     #[arg(short, long)]
     part: u8,
+
+    /// Operators to solve day-07 with, e.g. `+,*,||16`. These replace the
+    /// part's operators, so `--part` is ignored. Only day-07 takes them.
+    #[arg(short, long)]
+    operators: Option<String>,
 }
 
 #[tracing::instrument(level = "trace", skip())]
@@ -24,6 +29,21 @@ fn main() {
     #[cfg(feature = "dhat-heap")]
     let _profiler = dhat::Profiler::new_heap();
 
+    if let Some(operators) = &args.operators {
+        if args.day != "day-07" {
+            eprintln!("--operators only works with day-07, not {}", args.day);
+            std::process::exit(1);
+        }
+        match day_07::solver::run_with(operators) {
+            Ok(result) => print!("{}", result),
+            Err(error) => {
+                eprintln!("{}", error);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
     let result = match (args.day.as_str(), args.part) {
         ("day-01", 1) => day_01::part1::run(),
         ("day-01", 2) => day_01::part2::run(),
@@ -52,7 +72,7 @@ fn main() {
         ("day-13", 1) => day_13::part1::run(),
         ("day-13", 2) => day_13::part2::run(),
         ("day-14", 1) => day_14::part1::run(),
-        ("day-14", 2) => unimplemented!("Run `cargo run -p day-14` instead"),
+        ("day-14", 2) => day_14::part2::run(),
         ("day-15", 1) => day_15::part1::run(),
         ("day-15", 2) => day_15::part2::run(),
         ("day-16", 1) => day_16::part1::run(),